@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use rusty_jsc::private::kJSPropertyAttributeDontDelete;
+use rusty_jsc::{JSContext, JSObject, JSValue};
+
+fn main() {
+    let context = JSContext::default();
+    let headers = Rc::new(RefCell::new(BTreeMap::from([(
+        "content-type".to_string(),
+        "text/plain".to_string(),
+    )])));
+
+    let runtime = JSObject::new(&context);
+    runtime
+        .define_getter(
+            &context,
+            "memoryUsage",
+            |ctx, _this| {
+                let statistics = ctx.heap_statistics()?;
+                Ok(JSValue::number(ctx, statistics.heap_size as f64))
+            },
+            kJSPropertyAttributeDontDelete,
+        )
+        .unwrap();
+
+    let request = JSObject::new(&context);
+    request
+        .define_accessor(
+            &context,
+            "contentType",
+            {
+                let headers = headers.clone();
+                move |ctx, _this| {
+                    let headers = headers.borrow();
+                    Ok(JSValue::string(ctx, headers["content-type"].as_str()))
+                }
+            },
+            {
+                let headers = headers.clone();
+                move |ctx, _this, value| {
+                    let value = value.to_string(ctx)?.to_string();
+                    headers.borrow_mut().insert("content-type".to_string(), value);
+                    Ok(())
+                }
+            },
+            0,
+        )
+        .unwrap();
+
+    let global = context.get_global_object();
+    global.set_property(&context, "runtime", runtime.to_jsvalue()).unwrap();
+    global.set_property(&context, "request", request.to_jsvalue()).unwrap();
+
+    let result = context
+        .evaluate_script(
+            "request.contentType = 'application/json'; \
+             `${request.contentType}, heap ${runtime.memoryUsage > 0}`",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+    assert_eq!(headers.borrow()["content-type"], "application/json");
+}
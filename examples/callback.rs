@@ -1,21 +1,28 @@
-use rusty_jsc::{JSContext, JSValue, JSException};
+use rusty_jsc::{JSContext, JSException, JSObject, JSValue, Rest};
 use rusty_jsc_macros::callback;
 
+/// `first` is `None` when `foo` is called without arguments, and `rest`
+/// collects the other ones.
 #[callback]
 fn foo(
     ctx: JSContext,
-    _function: JSObject,
-    _this: JSObject,
-    args: &[JSValue],
+    first: Option<String>,
+    rest: Rest<JSValue>,
 ) -> Result<JSValue, JSException> {
     println!(
-        "hello from Rust land! len: {}, value[0]: {}",
-        args.len(),
-        args[0].to_string(&ctx).unwrap()
+        "hello from Rust land! value[0]: {}, {} more",
+        first.as_deref().unwrap_or("<missing>"),
+        rest.len()
     );
     Ok(JSValue::string(&ctx, "Returning a string to JS!"))
 }
 
+/// `repeat("ab")` is `"abab"`: `times` defaults to 2 when it is missing.
+#[callback]
+fn repeat(text: String, #[default = 2] times: usize) -> String {
+    text.repeat(times)
+}
+
 #[callback]
 fn foo2<A>(
     ctx: JSContext,
@@ -35,6 +42,8 @@ fn main() {
     let callback = JSValue::callback(&context, Some(foo));
     let global = context.get_global_object();
     global.set_property(&context, "foo", callback).unwrap();
+    let repeat = JSValue::callback(&context, Some(repeat));
+    global.set_property(&context, "repeat", repeat).unwrap();
     let foo = global
         .get_property(&context, "foo")
         .to_object(&context)
@@ -51,12 +60,14 @@ fn main() {
         "direct call: {}",
         result.unwrap().to_string(&context).unwrap()
     );
-    match context.evaluate_script("foo(1, 2, 3)", 1) {
-        Ok(value) => {
-            println!("{}", value.to_string(&context).unwrap());
-        }
-        Err(e) => {
-            println!("Uncaught: {}", e.to_string())
+    for script in ["foo(1, 2, 3)", "foo()", "repeat('ab')", "repeat('ab', 3)", "repeat()"] {
+        match context.evaluate_script(script, 1) {
+            Ok(value) => {
+                println!("{}", value.to_string(&context).unwrap());
+            }
+            Err(e) => {
+                println!("Uncaught: {}", e.to_string())
+            }
         }
     }
 }
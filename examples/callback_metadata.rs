@@ -0,0 +1,39 @@
+use rusty_jsc::{callback, JSContext, JSException, JSObject, JSValue, NewTarget};
+
+#[callback(name = "readFile", length = 2)]
+fn read_file(ctx: JSContext, args: &[JSValue]) -> Result<JSValue, JSException> {
+    let path = match args.first() {
+        Some(path) => path.to_string(&ctx)?.to_string(),
+        None => return Err("readFile requires a path".into()),
+    };
+    Ok(JSValue::string(&ctx, format!("contents of {}", path)))
+}
+
+/// Like `Date`, returns a string when called and an object with `new`.
+#[callback(name = "Timestamp")]
+fn timestamp(ctx: JSContext, this: JSObject, new_target: NewTarget) -> Result<JSValue, JSException> {
+    if !new_target.is_constructor_call() {
+        return Ok(JSValue::string(&ctx, "1970-01-01T00:00:00Z"));
+    }
+    this.set_property(&ctx, "seconds", JSValue::number(&ctx, 0.0))?;
+    Ok(JSValue::undefined(&ctx))
+}
+
+fn main() {
+    let context = JSContext::default();
+    let global = context.get_global_object();
+    let read_file = JSValue::callback_named(&context, READ_FILE_NAME, READ_FILE_LENGTH, Some(read_file));
+    global.set_property(&context, READ_FILE_NAME, read_file).unwrap();
+    let timestamp = JSValue::callback_named(&context, TIMESTAMP_NAME, TIMESTAMP_LENGTH, Some(timestamp));
+    global.set_property(&context, TIMESTAMP_NAME, timestamp).unwrap();
+
+    for script in [
+        "`${readFile.name} ${readFile.length}`",
+        "readFile('notes.txt')",
+        "Timestamp()",
+        "const t = new Timestamp(); `${t instanceof Timestamp} ${t.seconds}`",
+    ] {
+        let result = context.evaluate_script(script, 1).unwrap();
+        println!("{}: {}", script, result.to_string(&context).unwrap());
+    }
+}
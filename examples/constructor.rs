@@ -0,0 +1,45 @@
+use rusty_jsc::{constructor, JSClass, JSContext, JSException, JSObject, JSValue};
+
+struct Database {
+    path: String,
+}
+
+#[constructor]
+fn database(
+    ctx: JSContext,
+    _constructor: JSObject,
+    args: &[JSValue],
+) -> Result<Database, JSException> {
+    let path = match args.first() {
+        Some(path) => path.to_string(&ctx)?.to_string(),
+        None => return Err("Database requires a path".into()),
+    };
+    println!("opening {}", path);
+    Ok(Database { path })
+}
+
+impl Drop for Database {
+    fn drop(&mut self) {
+        println!("closing {}", self.path);
+    }
+}
+
+fn main() {
+    let context = JSContext::default();
+    let class = JSClass::builder("Database").build();
+    let database = JSObject::new_constructor(&context, &class, Some(database));
+    context
+        .get_global_object()
+        .set_property(&context, "Database", database.to_jsvalue())
+        .unwrap();
+
+    let result = context
+        .evaluate_script("const db = new Database('/tmp/app.db'); db instanceof Database", 1)
+        .unwrap();
+    assert!(result.to_bool(&context));
+
+    match context.evaluate_script("new Database()", 1) {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+}
@@ -0,0 +1,87 @@
+use rusty_jsc::{callback, FromJs, IntoJs, JSContext, JSException, JSValue};
+
+#[derive(Debug, FromJs)]
+#[js(rename_all = "camelCase")]
+struct FetchOptions {
+    url: String,
+    #[js(default)]
+    method: Method,
+    #[js(default = "default_timeout")]
+    timeout_ms: u32,
+    follow_redirects: Option<bool>,
+    #[js(skip)]
+    attempts: u32,
+}
+
+fn default_timeout() -> u32 {
+    30_000
+}
+
+#[derive(Debug, Default, FromJs, IntoJs)]
+enum Method {
+    #[default]
+    #[js(rename = "GET")]
+    Get,
+    #[js(rename = "POST")]
+    Post,
+}
+
+#[derive(IntoJs)]
+#[js(tag = "kind", rename_all = "camelCase")]
+enum Response {
+    #[js(rename_all = "camelCase")]
+    Ok { status_code: u16, body: String },
+    Redirect { location: String },
+    TimedOut,
+}
+
+fn respond(ctx: &JSContext, args: &[JSValue]) -> Result<JSValue, JSException> {
+    let options = match args.first() {
+        Some(options) => FetchOptions::from_js(ctx, options)?,
+        None => return Err("fetch requires options".into()),
+    };
+    let response = match options.url.as_str() {
+        "/old" if options.follow_redirects != Some(false) => Response::Redirect {
+            location: "/new".to_string(),
+        },
+        "/slow" => Response::TimedOut,
+        url => Response::Ok {
+            status_code: 200,
+            body: format!(
+                "{:?} {} (attempt {}, timeout {}ms)",
+                options.method,
+                url,
+                options.attempts + 1,
+                options.timeout_ms
+            ),
+        },
+    };
+    response.into_js(ctx)
+}
+
+#[callback]
+fn fetch(ctx: JSContext, args: &[JSValue]) -> Result<JSValue, JSException> {
+    respond(&ctx, args)
+}
+
+fn main() {
+    let context = JSContext::default();
+    let fetch = JSValue::callback(&context, Some(fetch));
+    context
+        .get_global_object()
+        .set_property(&context, "fetch", fetch)
+        .unwrap();
+
+    let result = context
+        .evaluate_script(
+            "JSON.stringify([fetch({ url: '/', method: 'POST', followRedirects: true }), fetch({ url: '/old', timeoutMs: 10 }), fetch({ url: '/slow' })])",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+
+    match context.evaluate_script("fetch({ method: 'GET' })", 1) {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+}
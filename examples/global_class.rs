@@ -0,0 +1,39 @@
+use rusty_jsc::private::{JSContextRef, JSObjectRef, JSStringRef, JSStringRetain, JSValueRef};
+use rusty_jsc::{JSClass, JSContext, JSString, JSValue};
+
+// Called for every global variable lookup, before the global object's own
+// properties are searched.
+unsafe extern "C" fn get_global(
+    ctx: JSContextRef,
+    _object: JSObjectRef,
+    name: JSStringRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let context = JSContext::from(ctx);
+    let name = JSString::from(JSStringRetain(name)).to_string();
+    match name.as_str() {
+        // Resolved lazily, on first access.
+        "hostVersion" => JSValue::string(&context, "1.0").into(),
+        "eval" => {
+            *exception = JSValue::string(&context, "eval is not allowed").into();
+            std::ptr::null()
+        }
+        // Falls back to the regular global object lookup.
+        _ => std::ptr::null(),
+    }
+}
+
+fn main() {
+    let class = JSClass::builder("Global")
+        .get_property(Some(get_global))
+        .build();
+    let context = JSContext::with_global_class(&class);
+
+    let version = context.evaluate_script("hostVersion", 1).unwrap();
+    println!("hostVersion: {}", version.to_string(&context).unwrap());
+
+    match context.evaluate_script("eval('1 + 1')", 1) {
+        Ok(value) => println!("{}", value.to_string(&context).unwrap()),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+}
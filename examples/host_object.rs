@@ -0,0 +1,65 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use rusty_jsc::{HostObject, JSContext, JSException, JSValue};
+
+/// Feature flags, looked up by scripts without being copied into an object.
+struct FeatureFlags {
+    flags: RefCell<BTreeMap<String, bool>>,
+}
+
+impl HostObject for FeatureFlags {
+    const NAME: &'static str = "FeatureFlags";
+
+    fn get(&self, context: &JSContext, name: &str) -> Result<Option<JSValue>, JSException> {
+        Ok(self
+            .flags
+            .borrow()
+            .get(name)
+            .map(|enabled| JSValue::boolean(context, *enabled)))
+    }
+
+    fn set(&self, context: &JSContext, name: &str, value: JSValue) -> Result<bool, JSException> {
+        if !value.is_boolean(context) {
+            return Err(JSException::type_error(context, format!("Flag `{}` must be a boolean", name)));
+        }
+        self.flags.borrow_mut().insert(name.to_string(), value.to_bool(context));
+        Ok(true)
+    }
+
+    fn delete(&self, _context: &JSContext, name: &str) -> Result<bool, JSException> {
+        Ok(self.flags.borrow_mut().remove(name).is_some())
+    }
+
+    fn keys(&self, _context: &JSContext) -> Vec<String> {
+        self.flags.borrow().keys().cloned().collect()
+    }
+}
+
+fn main() {
+    let context = JSContext::default();
+    let flags = FeatureFlags {
+        flags: RefCell::new(BTreeMap::from([
+            ("darkMode".to_string(), true),
+            ("betaSearch".to_string(), false),
+        ])),
+    };
+    context
+        .get_global_object()
+        .set_property(&context, "flags", flags.into_object(&context).to_jsvalue())
+        .unwrap();
+
+    let result = context
+        .evaluate_script(
+            "flags.newOnboarding = true; delete flags.betaSearch; \
+             `${Object.getOwnPropertyNames(flags)} ${'darkMode' in flags} ${flags.betaSearch}`",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+
+    match context.evaluate_script("flags.darkMode = 'yes'", 1) {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+}
@@ -0,0 +1,83 @@
+use std::cell::Cell;
+
+use rusty_jsc::{constructor, js_methods, JSContext, JSException, JSObject, JSValue, JsClass};
+
+#[derive(JsClass)]
+struct Shape {
+    #[js(get)]
+    name: String,
+}
+
+#[js_methods]
+impl Shape {
+    fn describe(&self) -> String {
+        format!("a shape called {}", self.name)
+    }
+}
+
+#[derive(JsClass)]
+#[js(extends = "Shape")]
+struct Circle {
+    #[js(parent)]
+    shape: Shape,
+    #[js(get, set)]
+    radius: Cell<f64>,
+}
+
+#[js_methods]
+impl Circle {
+    fn area(&self) -> f64 {
+        std::f64::consts::PI * self.radius.get() * self.radius.get()
+    }
+}
+
+#[constructor]
+fn new_shape(
+    ctx: JSContext,
+    _constructor: JSObject,
+    args: &[JSValue],
+) -> Result<Shape, JSException> {
+    let name = match args.first() {
+        Some(name) => name.to_string(&ctx)?.to_string(),
+        None => "shape".to_string(),
+    };
+    Ok(Shape { name })
+}
+
+fn main() {
+    let context = JSContext::default();
+    let global = context.get_global_object();
+    let circle = Circle {
+        shape: Shape {
+            name: "circle".to_string(),
+        },
+        radius: Cell::new(2.0),
+    };
+    global
+        .set_property(&context, "circle", circle.into_object(&context).to_jsvalue())
+        .unwrap();
+    let shape = JSObject::new_constructor(&context, &Shape::class(), Some(new_shape));
+    global
+        .set_property(&context, "Shape", shape.to_jsvalue())
+        .unwrap();
+
+    // Rust classes inherit from each other...
+    let result = context
+        .evaluate_script("`${circle.describe()}, area ${circle.area().toFixed(2)}, ${circle instanceof Shape}`", 1)
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+
+    // ...and scripts can extend them.
+    let result = context
+        .evaluate_script(
+            "class Square extends Shape {
+                constructor(side) { super('square'); this.side = side; }
+                area() { return this.side * this.side; }
+            }
+            const square = new Square(3);
+            `${square.describe()}, area ${square.area()}, ${square instanceof Square && square instanceof Shape}`",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+}
@@ -0,0 +1,67 @@
+use std::cell::Cell;
+
+use rusty_jsc::{js_methods, JSContext, JSException, JsClass};
+
+#[derive(JsClass)]
+struct Counter {
+    #[js(get)]
+    name: String,
+    #[js(get, set)]
+    count: Cell<u32>,
+}
+
+#[js_methods]
+impl Counter {
+    fn increment(&self, by: Option<u32>) -> u32 {
+        self.count.set(self.count.get() + by.unwrap_or(1));
+        self.count.get()
+    }
+
+    #[js(name = "toString")]
+    fn describe(&self) -> String {
+        format!("{}: {}", self.name, self.count.get())
+    }
+
+    fn reset(&self, ctx: &JSContext, to: f64) -> Result<(), JSException> {
+        if to < 0.0 {
+            return Err(JSException::type_error(ctx, "Can't reset to a negative count"));
+        }
+        self.count.set(to as u32);
+        Ok(())
+    }
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        println!("dropping {}", self.name);
+    }
+}
+
+fn main() {
+    let context = JSContext::default();
+    let counter = Counter {
+        name: "clicks".to_string(),
+        count: Cell::new(0),
+    };
+    context
+        .get_global_object()
+        .set_property(&context, "counter", counter.into_object(&context).to_jsvalue())
+        .unwrap();
+
+    let result = context
+        .evaluate_script("counter.increment(); counter.increment(2); counter.count += 1; `${counter}`", 1)
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+
+    match context.evaluate_script("counter.reset(-1)", 1) {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+    match context.evaluate_script("counter.increment.call({})", 1) {
+        Ok(_) => unreachable!(),
+        Err(e) => println!("Uncaught: {}", e),
+    }
+
+    context.evaluate_script("counter = undefined", 1).unwrap();
+    context.garbage_collect_sync();
+}
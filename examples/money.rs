@@ -0,0 +1,75 @@
+use rusty_jsc::{
+    js_methods, JSContext, JSException, JSValue, JsClass, JsConvert, JsHasInstance, JsMethods,
+};
+
+#[derive(JsClass)]
+#[js(convert)]
+struct Money {
+    cents: i64,
+    #[js(get)]
+    currency: String,
+}
+
+#[js_methods]
+impl Money {
+    fn amount(&self) -> f64 {
+        self.cents as f64 / 100.0
+    }
+}
+
+impl JsConvert for Money {
+    fn convert_to_number(&self, _context: &JSContext) -> Result<Option<f64>, JSException> {
+        Ok(Some(self.amount()))
+    }
+
+    fn convert_to_string(&self, _context: &JSContext) -> Result<Option<String>, JSException> {
+        Ok(Some(format!("{:.2} {}", self.amount(), self.currency)))
+    }
+}
+
+/// Exposed to scripts as `Money`, so that `value instanceof Money` works
+/// without a constructor.
+#[derive(JsClass)]
+#[js(name = "MoneyType", has_instance)]
+struct MoneyType;
+
+impl JsMethods for MoneyType {}
+
+impl JsHasInstance for MoneyType {
+    fn has_instance(&self, context: &JSContext, value: &JSValue) -> Result<bool, JSException> {
+        if !value.is_object(context) {
+            return Ok(false);
+        }
+        Ok(value.to_object(context)?.downcast_ref::<Money>(context).is_some())
+    }
+}
+
+fn main() {
+    let context = JSContext::default();
+    let global = context.get_global_object();
+    let price = Money {
+        cents: 1250,
+        currency: "EUR".to_string(),
+    };
+    let budget = Money {
+        cents: 2000,
+        currency: "EUR".to_string(),
+    };
+    global
+        .set_property(&context, "price", price.into_object(&context).to_jsvalue())
+        .unwrap();
+    global
+        .set_property(&context, "budget", budget.into_object(&context).to_jsvalue())
+        .unwrap();
+    global
+        .set_property(&context, "Money", MoneyType.into_object(&context).to_jsvalue())
+        .unwrap();
+
+    let result = context
+        .evaluate_script(
+            "`${price} < ${budget}: ${price < budget}, ${price instanceof Money}, ${({}) instanceof Money}`",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use rusty_jsc::{JSContext, JSValue, JSException};
+use rusty_jsc::{JSContext, JSException, JSObject, JSValue};
 use rusty_jsc_macros::callback;
 use std::fs;
 use std::path::PathBuf;
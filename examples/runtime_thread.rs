@@ -0,0 +1,35 @@
+use rusty_jsc::JSRuntimeThread;
+use std::thread;
+
+fn main() {
+    let runtime = JSRuntimeThread::new();
+
+    runtime
+        .run_blocking(|ctx| {
+            ctx.evaluate_script("var counter = 0;", 1).unwrap();
+        })
+        .unwrap();
+
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                runtime
+                    .run_blocking(|ctx| {
+                        let value = ctx.evaluate_script("++counter", 1).unwrap();
+                        value.to_number(ctx).unwrap()
+                    })
+                    .unwrap()
+            });
+        }
+    });
+
+    let counter = runtime
+        .run_blocking(|ctx| {
+            let value = ctx.evaluate_script("counter", 1).unwrap();
+            value.to_number(ctx).unwrap()
+        })
+        .unwrap();
+    assert_eq!(counter, 4f64);
+
+    runtime.shutdown();
+}
@@ -0,0 +1,79 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rusty_jsc::private::{kJSPropertyAttributeDontDelete, kJSPropertyAttributeDontEnum};
+use rusty_jsc::{callback, JSClass, JSContext, JSException, JSObject, JSValue};
+
+#[callback]
+fn platform(
+    ctx: JSContext,
+    _function: JSObject,
+    _this: JSObject,
+    _args: &[JSValue],
+) -> Result<JSValue, JSException> {
+    Ok(JSValue::string(&ctx, std::env::consts::OS))
+}
+
+fn main() {
+    let requests = Rc::new(Cell::new(0u32));
+    let log_level = Rc::new(Cell::new(1u32));
+
+    let class = JSClass::builder("Runtime")
+        .static_function("platform", Some(platform), kJSPropertyAttributeDontEnum)
+        .static_function_with_closure(
+            "request",
+            {
+                let requests = requests.clone();
+                move |ctx, _this, args| {
+                    requests.set(requests.get() + 1);
+                    let url = match args.first() {
+                        Some(url) => url.to_string(ctx)?.to_string(),
+                        None => return Err("request requires a URL".into()),
+                    };
+                    Ok(JSValue::string(ctx, format!("response from {}", url)))
+                }
+            },
+            kJSPropertyAttributeDontEnum,
+        )
+        .static_value_with_closure(
+            "requestCount",
+            {
+                let requests = requests.clone();
+                move |ctx, _this| Ok(JSValue::number(ctx, requests.get() as f64))
+            },
+            kJSPropertyAttributeDontDelete,
+        )
+        .static_value_with_closures(
+            "logLevel",
+            {
+                let log_level = log_level.clone();
+                move |ctx, _this| Ok(JSValue::number(ctx, log_level.get() as f64))
+            },
+            {
+                let log_level = log_level.clone();
+                move |ctx, _this, value| {
+                    log_level.set(value.to_number(ctx)? as u32);
+                    Ok(())
+                }
+            },
+            kJSPropertyAttributeDontDelete,
+        )
+        .build();
+
+    let context = JSContext::default();
+    let runtime = JSObject::new_with_data(&context, &class, ());
+    context
+        .get_global_object()
+        .set_property(&context, "runtime", runtime.to_jsvalue())
+        .unwrap();
+
+    let result = context
+        .evaluate_script(
+            "runtime.request('/a'); runtime.request('/b'); runtime.logLevel = 3; \
+             `${runtime.platform()} ${runtime.requestCount} ${runtime.logLevel}`",
+            1,
+        )
+        .unwrap();
+    println!("{}", result.to_string(&context).unwrap());
+    assert_eq!(log_level.get(), 3);
+}
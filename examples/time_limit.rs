@@ -0,0 +1,22 @@
+use rusty_jsc::{JSContext, TerminationReason};
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    let context = JSContext::default();
+
+    context.set_execution_time_limit(Some(Duration::from_millis(100)));
+    let err = context.evaluate_script("while (true) {}", 1).unwrap_err();
+    assert_eq!(err.termination(), Some(TerminationReason::TimedOut));
+    println!("{}", err);
+
+    context.set_execution_time_limit(None);
+    let handle = context.termination_handle();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        handle.terminate();
+    });
+    let err = context.evaluate_script("while (true) {}", 1).unwrap_err();
+    assert_eq!(err.termination(), Some(TerminationReason::Requested));
+    println!("{}", err);
+}
@@ -0,0 +1,50 @@
+use std::cell::Cell;
+
+use rusty_jsc::{callback, JSContext, JSValue, JsClass, JsMethods, This};
+
+#[derive(JsClass)]
+struct Stopwatch {
+    elapsed: Cell<f64>,
+}
+
+// The methods are plain callbacks, set on the object below.
+impl JsMethods for Stopwatch {}
+
+/// Only callable on a `Stopwatch`: anything else throws "Illegal invocation".
+#[callback]
+fn advance(this: &Stopwatch, seconds: f64) -> f64 {
+    this.elapsed.set(this.elapsed.get() + seconds);
+    this.elapsed.get()
+}
+
+#[callback]
+fn reset(stopwatch: This<Stopwatch>) -> JSValue {
+    stopwatch.elapsed.set(0.0);
+    // The receiver itself, for chaining.
+    stopwatch.object().to_jsvalue()
+}
+
+fn main() {
+    let context = JSContext::default();
+    let stopwatch = Stopwatch { elapsed: Cell::new(0.0) }.into_object(&context);
+    let advance = JSValue::callback_named(&context, ADVANCE_NAME, ADVANCE_LENGTH, Some(advance));
+    stopwatch.set_property(&context, ADVANCE_NAME, advance).unwrap();
+    let reset = JSValue::callback_named(&context, RESET_NAME, RESET_LENGTH, Some(reset));
+    stopwatch.set_property(&context, RESET_NAME, reset).unwrap();
+    context
+        .get_global_object()
+        .set_property(&context, "stopwatch", stopwatch.to_jsvalue())
+        .unwrap();
+
+    for script in [
+        "stopwatch.advance(1.5); stopwatch.advance(2)",
+        "stopwatch.reset().advance(0.5)",
+        "stopwatch.advance.call({}, 1)",
+        "const { advance } = stopwatch; advance(1)",
+    ] {
+        match context.evaluate_script(script, 1) {
+            Ok(value) => println!("{}: {}", script, value.to_string(&context).unwrap()),
+            Err(e) => println!("{}: Uncaught: {}", script, e),
+        }
+    }
+}
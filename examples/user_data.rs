@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use rusty_jsc::{callback, JSContext, JSException, JSValue};
+
+/// Service handles shared by the host functions.
+struct Services {
+    config: HashMap<String, String>,
+    lookups: usize,
+}
+
+/// `services` is borrowed from the context's user data for the duration of
+/// the call.
+#[callback]
+fn get_config(
+    ctx: JSContext,
+    args: &[JSValue],
+    #[state] services: &mut Services,
+) -> Result<JSValue, JSException> {
+    let key = match args.first() {
+        Some(key) => key.to_string(&ctx)?.to_string(),
+        None => return Err("getConfig requires a key".into()),
+    };
+    services.lookups += 1;
+    Ok(match services.config.get(&key) {
+        Some(value) => JSValue::string(&ctx, value.as_str()),
+        None => JSValue::undefined(&ctx),
+    })
+}
+
+fn main() {
+    let context = JSContext::default();
+    context.set_user_data(Services {
+        config: HashMap::from([("region".to_string(), "eu-west-1".to_string())]),
+        lookups: 0,
+    });
+    let get_config = JSValue::callback(&context, Some(get_config));
+    context
+        .get_global_object()
+        .set_property(&context, "getConfig", get_config)
+        .unwrap();
+
+    let result = context.evaluate_script("getConfig('region')", 1).unwrap();
+    println!("region: {}", result.to_string(&context).unwrap());
+
+    let services = context.user_data::<Services>().unwrap();
+    assert_eq!(services.borrow().lookups, 1);
+}
@@ -0,0 +1,114 @@
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::{Attribute, Lit, LitStr, Meta, NestedMeta};
+
+/// Every option of the `#[js(...)]` attribute.
+const ALL_OPTIONS: &[&str] = &[
+    "name", "get", "set", "skip", "rename", "rename_all", "default", "tag", "convert", "has_instance", "extends", "parent",
+];
+
+/// What a `#[js(...)]` attribute is placed on.
+#[derive(Clone, Copy)]
+pub(crate) enum Target {
+    Item,
+    Field,
+    Variant,
+    Method,
+}
+
+impl Target {
+    /// Every option some macro reads on this target. Derives on the same item
+    /// share the attribute, so each one ignores the options of the others, but
+    /// an option none of them reads here is an error.
+    fn known_options(self) -> &'static [&'static str] {
+        match self {
+            Target::Item => &["name", "convert", "has_instance", "extends", "rename_all", "tag"],
+            Target::Field => &["get", "set", "name", "parent", "rename", "default", "skip"],
+            Target::Variant => &["rename", "rename_all"],
+            Target::Method => &["name", "skip"],
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Target::Item => "a type",
+            Target::Field => "a field",
+            Target::Variant => "a variant",
+            Target::Method => "a method",
+        }
+    }
+}
+
+/// The options given in the `#[js(...)]` attributes of an item, either flags
+/// like `get` or string values like `name = "..."`.
+pub(crate) struct JsAttrs {
+    entries: Vec<(String, Span, Option<LitStr>)>,
+}
+
+impl JsAttrs {
+    /// Parses the `#[js(...)]` attributes placed on `target`, keeping the
+    /// options in `allowed`. Options that no macro reads on `target` are
+    /// rejected.
+    pub(crate) fn parse(attrs: &[Attribute], target: Target, allowed: &[&str]) -> syn::Result<Self> {
+        let mut entries = Vec::new();
+        for attr in attrs.iter().filter(|attr| is_js_attr(attr)) {
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                meta => return Err(syn::Error::new(meta.span(), "expected #[js(...)]")),
+            };
+            for nested in list.nested {
+                let (path, value) = match nested {
+                    NestedMeta::Meta(Meta::Path(path)) => (path, None),
+                    NestedMeta::Meta(Meta::NameValue(name_value)) => match name_value.lit {
+                        Lit::Str(value) => (name_value.path, Some(value)),
+                        lit => return Err(syn::Error::new(lit.span(), "expected a string")),
+                    },
+                    nested => return Err(syn::Error::new(nested.span(), "unexpected option")),
+                };
+                let key = match path.get_ident().map(ToString::to_string) {
+                    Some(key) if allowed.contains(&key.as_str()) => key,
+                    Some(key) if target.known_options().contains(&key.as_str()) => continue,
+                    Some(key) if ALL_OPTIONS.contains(&key.as_str()) => {
+                        return Err(syn::Error::new(
+                            path.span(),
+                            format!("`{}` has no effect on {}", key, target.describe()),
+                        ))
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            path.span(),
+                            format!("unknown option, expected one of: {}", allowed.join(", ")),
+                        ))
+                    }
+                };
+                entries.push((key, path.span(), value));
+            }
+        }
+        Ok(JsAttrs { entries })
+    }
+
+    /// Checks if the option `key` is present.
+    pub(crate) fn flag(&self, key: &str) -> bool {
+        self.entries.iter().any(|(k, _, _)| k == key)
+    }
+
+    /// Returns an error pointing at the option `key` if it is present.
+    pub(crate) fn reject(&self, key: &str, message: &str) -> syn::Result<()> {
+        match self.entries.iter().find(|(k, _, _)| k == key) {
+            Some((_, span, _)) => Err(syn::Error::new(*span, message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the string value of the option `key`, if it has one.
+    pub(crate) fn string(&self, key: &str) -> Option<LitStr> {
+        self.entries
+            .iter()
+            .find(|(k, _, _)| k == key)
+            .and_then(|(_, _, value)| value.clone())
+    }
+}
+
+pub(crate) fn is_js_attr(attr: &Attribute) -> bool {
+    attr.path.is_ident("js")
+}
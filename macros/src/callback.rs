@@ -0,0 +1,351 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::parse::{ParseStream, Parser};
+use syn::{Attribute, AttributeArgs, Expr, FnArg, ItemFn, Lit, LitStr, Meta, NestedMeta, Pat, Token, Type};
+
+/// What the trampoline passes for a parameter of the callback.
+enum Param {
+    Context { by_ref: bool },
+    Function,
+    This,
+    Arguments,
+    NewTarget,
+    State { ty: Box<Type>, mutable: bool },
+    /// `this`, downcast to a `This<T>`, or to the `&T` given here.
+    Receiver { ty: Option<Box<Type>> },
+    /// The JavaScript argument at `index`, converted with `FromJs`.
+    Argument { index: usize, optional: bool, default: Option<Box<Expr>> },
+    /// The JavaScript arguments from `index` on, as a `Rest` or a `Vec`.
+    Rest { index: usize, wrapped: bool },
+}
+
+impl Param {
+    /// Checks if the JavaScript argument must be passed.
+    fn is_required(&self) -> bool {
+        matches!(self, Param::Argument { optional: false, default: None, .. })
+    }
+}
+
+fn last_segment_is(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        _ => false,
+    }
+}
+
+fn is_arguments(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Slice(slice) => last_segment_is(&slice.elem, "JSValue"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn param_name(pat: &Pat) -> Option<String> {
+    match pat {
+        Pat::Ident(ident) => Some(ident.ident.to_string().trim_start_matches('_').to_string()),
+        _ => None,
+    }
+}
+
+fn is_default(attr: &Attribute) -> bool {
+    attr.path.is_ident("default")
+}
+
+fn is_state(attr: &Attribute) -> bool {
+    attr.path.is_ident("state")
+}
+
+/// Parses `#[default = expr]`.
+fn parse_default(attr: &Attribute) -> syn::Result<Expr> {
+    let parser = |input: ParseStream| {
+        input.parse::<Token![=]>()?;
+        input.parse::<Expr>()
+    };
+    parser.parse2(attr.tokens.clone())
+}
+
+/// Works out what each parameter stands for from its type.
+fn classify(func: &ItemFn) -> syn::Result<Vec<Param>> {
+    let mut params = Vec::new();
+    let mut objects = 0;
+    let mut arguments = 0;
+    for input in &func.sig.inputs {
+        let input = match input {
+            FnArg::Typed(input) => input,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(receiver.span(), "callbacks can't take `self`"))
+            }
+        };
+        let ty = &*input.ty;
+        let param = if let Some(attr) = input.attrs.iter().find(|attr| is_state(attr)) {
+            match ty {
+                Type::Reference(reference) => Param::State {
+                    ty: reference.elem.clone(),
+                    mutable: reference.mutability.is_some(),
+                },
+                _ => return Err(syn::Error::new(attr.span(), "`#[state]` takes a `&T` or a `&mut T`")),
+            }
+        } else if last_segment_is(ty, "JSContext") {
+            Param::Context { by_ref: false }
+        } else if matches!(ty, Type::Reference(r) if last_segment_is(&r.elem, "JSContext")) {
+            Param::Context { by_ref: true }
+        } else if last_segment_is(ty, "NewTarget") {
+            Param::NewTarget
+        } else if is_arguments(ty) {
+            Param::Arguments
+        } else if last_segment_is(ty, "JSObject") {
+            objects += 1;
+            match param_name(&input.pat).as_deref() {
+                Some("this") => Param::This,
+                Some("function") => Param::Function,
+                // Unnamed objects follow the classic order: the function, then `this`.
+                _ if objects == 1 => Param::Function,
+                _ if objects == 2 => Param::This,
+                _ => {
+                    return Err(syn::Error::new(
+                        input.span(),
+                        "a callback takes at most two objects, the function and `this`",
+                    ))
+                }
+            }
+        } else if last_segment_is(ty, "This") {
+            Param::Receiver { ty: None }
+        } else if let (Type::Reference(reference), Some("this")) = (ty, param_name(&input.pat).as_deref()) {
+            if reference.mutability.is_some() {
+                return Err(syn::Error::new(ty.span(), "`this` can only be borrowed as `&T`"));
+            }
+            Param::Receiver { ty: Some(reference.elem.clone()) }
+        } else if let Type::Reference(reference) = ty {
+            let message = if last_segment_is(&reference.elem, "str") {
+                "take a `String` for a string argument"
+            } else {
+                "arguments are taken by value; mark `&T` or `&mut T` with `#[state]` to borrow the state \
+                 registered with `JSContext::set_user_data`"
+            };
+            return Err(syn::Error::new(ty.span(), message));
+        } else if last_segment_is(ty, "Rest") {
+            arguments += 1;
+            Param::Rest { index: arguments - 1, wrapped: true }
+        } else {
+            let default = match input.attrs.iter().find(|attr| is_default(attr)) {
+                Some(attr) => Some(Box::new(parse_default(attr)?)),
+                None => None,
+            };
+            arguments += 1;
+            Param::Argument {
+                index: arguments - 1,
+                optional: last_segment_is(ty, "Option"),
+                default,
+            }
+        };
+        params.push(param);
+    }
+
+    // A trailing `Vec` collects the remaining arguments, and nothing can follow
+    // the parameter collecting them.
+    let last = params
+        .iter()
+        .rposition(|param| matches!(param, Param::Argument { .. } | Param::Rest { .. }));
+    if let Some(last) = last {
+        if let (Param::Argument { index, default: None, .. }, FnArg::Typed(input)) =
+            (&params[last], &func.sig.inputs[last])
+        {
+            if last_segment_is(&input.ty, "Vec") {
+                params[last] = Param::Rest { index: *index, wrapped: false };
+            }
+        }
+        for (param, input) in params.iter().zip(&func.sig.inputs).take(last) {
+            if matches!(param, Param::Rest { .. }) {
+                return Err(syn::Error::new(input.span(), "`Rest` must be the last argument"));
+            }
+        }
+    }
+    Ok(params)
+}
+
+/// The `name` and `length` the function gets in JavaScript.
+struct Metadata {
+    name: LitStr,
+    length: u32,
+}
+
+impl Metadata {
+    /// Parses the arguments of `#[callback]`. Like in JavaScript, the length
+    /// defaults to the number of arguments before the first optional one.
+    fn parse(args: AttributeArgs, func: &ItemFn, params: &[Param]) -> syn::Result<Self> {
+        let ident = &func.sig.ident;
+        let length = params
+            .iter()
+            .filter(|param| matches!(param, Param::Argument { .. } | Param::Rest { .. }))
+            .take_while(|param| param.is_required())
+            .count();
+        let mut metadata = Metadata {
+            name: LitStr::new(&ident.to_string(), ident.span()),
+            length: length as u32,
+        };
+        for arg in args {
+            let meta = match arg {
+                NestedMeta::Meta(Meta::NameValue(meta)) => meta,
+                arg => return Err(syn::Error::new(arg.span(), "expected `name = \"...\"` or `length = ...`")),
+            };
+            match &meta.lit {
+                Lit::Str(name) if meta.path.is_ident("name") => metadata.name = name.clone(),
+                Lit::Int(length) if meta.path.is_ident("length") => metadata.length = length.base10_parse()?,
+                _ => return Err(syn::Error::new(meta.span(), "expected `name = \"...\"` or `length = ...`")),
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+pub(crate) fn callback(args: AttributeArgs, func: ItemFn) -> syn::Result<TokenStream> {
+    let name = &func.sig.ident;
+    let params = classify(&func)?;
+    let metadata = Metadata::parse(args, &func, &params)?;
+
+    // The function is kept as is, nested in the trampoline, so that `?` and `return` work in its body.
+    let mut inner = func.clone();
+    inner.sig.ident = format_ident!("__callback_fn");
+    inner.vis = syn::Visibility::Inherited;
+    inner.attrs = vec![];
+    for input in &mut inner.sig.inputs {
+        if let FnArg::Typed(input) = input {
+            input.attrs.retain(|attr| !is_default(attr) && !is_state(attr));
+        }
+    }
+    let (_, ty_generics, _) = func.sig.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+
+    let js_name = &metadata.name;
+    let mut setup = Vec::new();
+    // Missing arguments are an error up to the last required one.
+    let required = params
+        .iter()
+        .filter_map(|param| match param {
+            Param::Argument { index, .. } if param.is_required() => Some(index + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    if required > 0 {
+        setup.push(quote! {
+            rusty_jsc::private::check_arguments(__ctx, #js_name, &__args, #required)?;
+        });
+    }
+    if params.iter().any(|param| matches!(param, Param::Receiver { .. })) {
+        setup.push(quote! {
+            let __receiver: rusty_jsc::JSObject = __this_object.into();
+        });
+    }
+    // Everything but the state is ready before the state is borrowed, so that
+    // the conversions can run callbacks using it. The borrows end with the
+    // call.
+    let mut values = Vec::new();
+    let mut borrows = Vec::new();
+    for (index, param) in params.iter().enumerate() {
+        let value = match param {
+            Param::Context { by_ref: false } => quote!(__ctx.clone()),
+            Param::Context { by_ref: true } => quote!(__ctx),
+            Param::Function => quote!(__function.into()),
+            Param::This => quote!(__this_object.into()),
+            Param::Arguments => quote!(&__args),
+            Param::NewTarget => quote!(rusty_jsc::NewTarget::of(&__function.into())),
+            Param::Receiver { ty: None } => quote!(rusty_jsc::This::new(__ctx, &__receiver)?),
+            Param::Receiver { ty: Some(ty) } => {
+                quote!(rusty_jsc::This::<#ty>::new(__ctx, &__receiver)?.into_inner())
+            }
+            Param::Argument { index, default: None, .. } => {
+                quote!(rusty_jsc::private::argument(__ctx, &__args, #index)?)
+            }
+            Param::Argument { index, default: Some(default), .. } => {
+                quote!(rusty_jsc::private::argument_or_else(__ctx, &__args, #index, || #default)?)
+            }
+            Param::Rest { index, wrapped: true } => {
+                quote!(rusty_jsc::Rest(rusty_jsc::private::rest(__ctx, &__args, #index)?))
+            }
+            Param::Rest { index, wrapped: false } => {
+                quote!(rusty_jsc::private::rest(__ctx, &__args, #index)?)
+            }
+            Param::State { ty, mutable } => {
+                let state = format_ident!("__state_{}", index);
+                let borrowed = format_ident!("__state_ref_{}", index);
+                setup.push(quote! {
+                    let #state = rusty_jsc::private::state::<#ty>(__ctx)?;
+                });
+                if *mutable {
+                    borrows.push(quote! {
+                        let mut #borrowed = rusty_jsc::private::borrow_state_mut(&#state)?;
+                    });
+                    values.push(quote!(&mut *#borrowed));
+                } else {
+                    borrows.push(quote! {
+                        let #borrowed = rusty_jsc::private::borrow_state(&#state)?;
+                    });
+                    values.push(quote!(&*#borrowed));
+                }
+                continue;
+            }
+        };
+        let local = format_ident!("__param_{}", index);
+        setup.push(quote! { let #local = #value; });
+        values.push(quote!(#local));
+    }
+
+    let result = quote! {
+        unsafe extern "C" fn #name(
+            __base_ctx: rusty_jsc::private::JSContextRef,
+            __function: rusty_jsc::private::JSObjectRef,
+            __this_object: rusty_jsc::private::JSObjectRef,
+            __argument_count: rusty_jsc::private::size_t,
+            __arguments: *const rusty_jsc::private::JSValueRef,
+            __exception: *mut rusty_jsc::private::JSValueRef,
+        ) -> rusty_jsc::private::JSValueRef {
+            #inner
+
+            let __args = rusty_jsc::private::arguments(__argument_count, __arguments);
+            rusty_jsc::private::run_callback(__base_ctx, __exception, |__ctx| {
+                #(#setup)*
+                let __result = {
+                    #(#borrows)*
+                    __callback_fn #turbofish(#(#values),*)
+                };
+                rusty_jsc::private::IntoCallbackResult::into_callback_result(__result, __ctx)
+            })
+        }
+    };
+    // We do this so we make sure the function generics and other properties are preserved
+    let mut new_func = syn::parse2::<ItemFn>(result)?;
+    new_func.attrs = func.attrs;
+    new_func.vis = func.vis;
+    new_func.sig.generics = func.sig.generics;
+
+    // Consts are valid both next to a function and in an impl block, unlike a
+    // module named after the function.
+    let vis = &new_func.vis;
+    let length = metadata.length;
+    let upper = name.to_string().trim_start_matches("r#").to_uppercase();
+    let name_const = format_ident!("{}_NAME", upper);
+    let length_const = format_ident!("{}_LENGTH", upper);
+    let name_doc = format!("The JavaScript `name` of `{}`.", name);
+    let length_doc = format!("The JavaScript `length` of `{}`.", name);
+    let cfgs = new_func.attrs.iter().filter(|attr| attr.path.is_ident("cfg"));
+    let cfgs = quote!(#(#cfgs)*);
+    Ok(quote! {
+        #new_func
+
+        #cfgs
+        #[doc = #name_doc]
+        #vis const #name_const: &str = #js_name;
+        #cfgs
+        #[doc = #length_doc]
+        #vis const #length_const: u32 = #length;
+    })
+}
@@ -0,0 +1,292 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, FnArg, ImplItem, ItemImpl, LitStr, Type};
+
+use crate::attrs::{is_js_attr, JsAttrs, Target};
+
+pub(crate) fn derive_js_class(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "JsClass can't be derived for generic types",
+        ));
+    }
+    let attrs = JsAttrs::parse(&input.attrs, Target::Item, &["name", "convert", "has_instance", "extends"])?;
+    let class_name = attrs
+        .string("name")
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+
+    let mut definitions = Vec::new();
+    if attrs.flag("convert") {
+        definitions.push(quote! {
+            let builder = builder.convert_to_type(Some(rusty_jsc::private::convert_to_type::<#ident>));
+        });
+    }
+    if attrs.flag("has_instance") {
+        definitions.push(quote! {
+            let builder = builder.has_instance(Some(rusty_jsc::private::has_instance::<#ident>));
+        });
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            _ => vec![],
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.span(),
+                "JsClass can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut parent_field = None;
+    for field in fields {
+        let attrs = JsAttrs::parse(&field.attrs, Target::Field, &["get", "set", "name", "parent"])?;
+        if attrs.flag("parent") {
+            parent_field = field.ident.as_ref();
+        }
+        if !attrs.flag("get") && !attrs.flag("set") {
+            continue;
+        }
+        if !attrs.flag("get") {
+            return Err(syn::Error::new(field.span(), "a property with a setter needs a getter too"));
+        }
+        let field_ident = field.ident.as_ref().unwrap();
+        let property_name = attrs
+            .string("name")
+            .unwrap_or_else(|| LitStr::new(&field_ident.to_string(), field_ident.span()));
+        let getter = format_ident!("__rusty_jsc_get_{}", field_ident);
+        let setter = format_ident!("__rusty_jsc_set_{}", field_ident);
+
+        let (setter_fn, setter_ref) = if attrs.flag("set") {
+            let setter_fn = quote! {
+                unsafe extern "C" fn #setter(
+                    __base_ctx: rusty_jsc::private::JSContextRef,
+                    __object: rusty_jsc::private::JSObjectRef,
+                    _: rusty_jsc::private::JSStringRef,
+                    __value: rusty_jsc::private::JSValueRef,
+                    __exception: *mut rusty_jsc::private::JSValueRef,
+                ) -> bool {
+                    let __ctx = rusty_jsc::JSContext::from(__base_ctx);
+                    let __this: rusty_jsc::JSObject = __object.into();
+                    let __value: rusty_jsc::JSValue = __value.into();
+                    let __result = <#ident as rusty_jsc::JsClass>::from_this(&__ctx, &__this).and_then(|__receiver| {
+                        rusty_jsc::SetFromJs::set_from_js(&__receiver.#field_ident, &__ctx, &__value)
+                    });
+                    match __result {
+                        Ok(()) => true,
+                        Err(err) => {
+                            rusty_jsc::private::throw(&__ctx, __exception, err);
+                            false
+                        }
+                    }
+                }
+            };
+            (setter_fn, quote! { Some(#setter) })
+        } else {
+            (quote! {}, quote! { None })
+        };
+
+        // Cloning a `RefCell` panics while it is mutably borrowed, and a panic
+        // can't unwind out of the callback.
+        let read = if is_ref_cell(&field.ty) {
+            quote! {
+                __receiver
+                    .#field_ident
+                    .try_borrow()
+                    .map_err(|_| rusty_jsc::JSException::from("Can't read a property that is being modified"))
+                    .and_then(|__field| rusty_jsc::IntoJs::into_js(::std::clone::Clone::clone(&*__field), &__ctx))
+            }
+        } else {
+            quote! { rusty_jsc::IntoJs::into_js(::std::clone::Clone::clone(&__receiver.#field_ident), &__ctx) }
+        };
+        definitions.push(quote! {
+            unsafe extern "C" fn #getter(
+                __base_ctx: rusty_jsc::private::JSContextRef,
+                __object: rusty_jsc::private::JSObjectRef,
+                _: rusty_jsc::private::JSStringRef,
+                __exception: *mut rusty_jsc::private::JSValueRef,
+            ) -> rusty_jsc::private::JSValueRef {
+                let __ctx = rusty_jsc::JSContext::from(__base_ctx);
+                let __this: rusty_jsc::JSObject = __object.into();
+                let __result = <#ident as rusty_jsc::JsClass>::from_this(&__ctx, &__this).and_then(|__receiver| {
+                    #read
+                });
+                match __result {
+                    Ok(value) => value.into(),
+                    Err(err) => {
+                        rusty_jsc::private::throw(&__ctx, __exception, err);
+                        std::ptr::null_mut()
+                    }
+                }
+            }
+            #setter_fn
+            let builder = builder.static_value(
+                #property_name,
+                Some(#getter),
+                #setter_ref,
+                rusty_jsc::private::kJSPropertyAttributeDontDelete,
+            );
+        });
+    }
+
+    let inheritance = match attrs.string("extends") {
+        Some(parent) => {
+            let parent: Type = parent.parse()?;
+            let parent_field = parent_field.ok_or_else(|| {
+                syn::Error::new(
+                    input.span(),
+                    "a class extending another needs a #[js(parent)] field holding the parent value",
+                )
+            })?;
+            quote! {
+                fn parent_class() -> Option<rusty_jsc::JSClass> {
+                    Some(<#parent as rusty_jsc::JsClass>::class())
+                }
+
+                fn upcast(&self) -> Option<&dyn ::std::any::Any> {
+                    let parent: &#parent = &self.#parent_field;
+                    Some(parent)
+                }
+            }
+        }
+        None => quote! {},
+    };
+
+    Ok(quote! {
+        impl rusty_jsc::JsClass for #ident {
+            const NAME: &'static str = #class_name;
+
+            #inheritance
+
+            fn define_properties(builder: rusty_jsc::JSClassBuilder) -> rusty_jsc::JSClassBuilder {
+                #(#definitions)*
+                builder
+            }
+        }
+    })
+}
+
+fn is_context(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "JSContext"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_ref_cell(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "RefCell"),
+        _ => false,
+    }
+}
+
+pub(crate) fn js_methods(mut item: ItemImpl) -> syn::Result<TokenStream> {
+    if !item.generics.params.is_empty() || item.trait_.is_some() {
+        return Err(syn::Error::new(
+            item.span(),
+            "#[js_methods] only supports inherent impls of non-generic types",
+        ));
+    }
+    let self_ty = &item.self_ty;
+
+    let mut methods = Vec::new();
+    for impl_item in &mut item.items {
+        let method = match impl_item {
+            ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        let attrs = JsAttrs::parse(&method.attrs, Target::Method, &["name", "skip"])?;
+        method.attrs.retain(|attr| !is_js_attr(attr));
+        let is_ref_self = matches!(
+            method.sig.inputs.first(),
+            Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() && receiver.mutability.is_none()
+        );
+        if attrs.flag("skip") || !is_ref_self {
+            continue;
+        }
+
+        let method_ident = &method.sig.ident;
+        let function_name = attrs
+            .string("name")
+            .unwrap_or_else(|| LitStr::new(&method_ident.to_string(), method_ident.span()));
+        let trampoline = format_ident!("__rusty_jsc_method_{}", method_ident);
+
+        let mut index = 0usize;
+        let mut arguments = Vec::new();
+        for input in method.sig.inputs.iter().skip(1) {
+            let ty = match input {
+                FnArg::Typed(typed) => &typed.ty,
+                FnArg::Receiver(_) => unreachable!(),
+            };
+            if is_context(ty) {
+                arguments.push(quote! { &__ctx });
+            } else {
+                arguments.push(quote! {
+                    rusty_jsc::private::argument::<#ty>(&__ctx, &__args, #index)?
+                });
+                index += 1;
+            }
+        }
+
+        methods.push(quote! {
+            unsafe extern "C" fn #trampoline(
+                __base_ctx: rusty_jsc::private::JSContextRef,
+                _: rusty_jsc::private::JSObjectRef,
+                __this_object: rusty_jsc::private::JSObjectRef,
+                __argument_count: rusty_jsc::private::size_t,
+                __arguments: *const rusty_jsc::private::JSValueRef,
+                __exception: *mut rusty_jsc::private::JSValueRef,
+            ) -> rusty_jsc::private::JSValueRef {
+                let __ctx = rusty_jsc::JSContext::from(__base_ctx);
+                let __this: rusty_jsc::JSObject = __this_object.into();
+                let __args = rusty_jsc::private::arguments(__argument_count, __arguments);
+                let __result = (|| -> Result<rusty_jsc::JSValue, rusty_jsc::JSException> {
+                    let __receiver = <#self_ty as rusty_jsc::JsClass>::from_this(&__ctx, &__this)?;
+                    rusty_jsc::private::IntoCallbackResult::into_callback_result(
+                        __receiver.#method_ident(#(#arguments),*),
+                        &__ctx,
+                    )
+                })();
+                match __result {
+                    Ok(value) => value.into(),
+                    Err(err) => {
+                        rusty_jsc::private::throw(&__ctx, __exception, err);
+                        rusty_jsc::JSValue::undefined(&__ctx).into()
+                    }
+                }
+            }
+            let builder = builder.static_function(
+                #function_name,
+                Some(#trampoline),
+                rusty_jsc::private::kJSPropertyAttributeDontEnum,
+            );
+        });
+    }
+
+    Ok(quote! {
+        #item
+
+        impl rusty_jsc::JsMethods for #self_ty {
+            fn define_methods(builder: rusty_jsc::JSClassBuilder) -> rusty_jsc::JSClassBuilder {
+                #(#methods)*
+                builder
+            }
+        }
+    })
+}
@@ -0,0 +1,445 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Data, DataEnum, DeriveInput, Field, Fields, Generics, Ident, LitStr, Type,
+    Variant,
+};
+
+use crate::attrs::{JsAttrs, Target};
+
+/// A naming convention for `#[js(rename_all = "...")]`.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    None,
+    CamelCase,
+    PascalCase,
+    SnakeCase,
+    KebabCase,
+}
+
+impl RenameRule {
+    fn parse(attrs: &JsAttrs) -> syn::Result<Self> {
+        let value = match attrs.string("rename_all") {
+            Some(value) => value,
+            None => return Ok(RenameRule::None),
+        };
+        match value.value().as_str() {
+            "camelCase" => Ok(RenameRule::CamelCase),
+            "PascalCase" => Ok(RenameRule::PascalCase),
+            "snake_case" => Ok(RenameRule::SnakeCase),
+            "kebab-case" => Ok(RenameRule::KebabCase),
+            _ => Err(syn::Error::new(
+                value.span(),
+                "expected camelCase, PascalCase, snake_case or kebab-case",
+            )),
+        }
+    }
+
+    /// Renames a Rust identifier, either a snake_case field or a PascalCase
+    /// variant.
+    fn apply(self, ident: &str) -> String {
+        let mut words = Vec::new();
+        for part in ident.split('_').filter(|part| !part.is_empty()) {
+            let mut word = String::new();
+            for c in part.chars() {
+                if c.is_uppercase() && !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                word.push(c);
+            }
+            words.push(word);
+        }
+        let capitalize = |word: &String| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+                None => String::new(),
+            }
+        };
+        match self {
+            RenameRule::None => ident.to_string(),
+            RenameRule::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+                .collect(),
+            RenameRule::PascalCase => words.iter().map(capitalize).collect(),
+            RenameRule::SnakeCase => words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_"),
+            RenameRule::KebabCase => words.iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("-"),
+        }
+    }
+}
+
+fn js_name(attrs: &JsAttrs, ident: &Ident, rule: RenameRule) -> LitStr {
+    attrs
+        .string("rename")
+        .unwrap_or_else(|| LitStr::new(&rule.apply(&ident.to_string()), ident.span()))
+}
+
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Adds `bound` to every type parameter.
+fn add_bounds(mut generics: Generics, bound: syn::TypeParamBound) -> Generics {
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
+    generics
+}
+
+/// The value of a field left out of the JavaScript object, if it has one.
+fn default_value(attrs: &JsAttrs) -> syn::Result<Option<TokenStream>> {
+    if let Some(path) = attrs.string("default") {
+        let path: syn::ExprPath = path.parse()?;
+        return Ok(Some(quote! { #path() }));
+    }
+    Ok(attrs
+        .flag("default")
+        .then(|| quote! { ::std::default::Default::default() }))
+}
+
+/// Reads the named fields of a struct or of an enum variant from `__object`
+/// and builds the value with `constructor`.
+fn read_fields(
+    fields: &[&Field],
+    rule: RenameRule,
+    constructor: TokenStream,
+) -> syn::Result<TokenStream> {
+    let mut reads = Vec::new();
+    let mut inits = Vec::new();
+    for field in fields {
+        let attrs = JsAttrs::parse(&field.attrs, Target::Field, &["rename", "default", "skip"])?;
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let local = field_local(ident);
+        let default = default_value(&attrs)?;
+        inits.push(quote! { #ident: #local });
+
+        if attrs.flag("skip") {
+            let default = default.unwrap_or_else(|| quote! { ::std::default::Default::default() });
+            reads.push(quote! { let #local = #default; });
+            continue;
+        }
+        let name = js_name(&attrs, ident, rule);
+        let missing = match default {
+            Some(default) => default,
+            None if is_option(ty) => quote! { None },
+            None => quote! {
+                return Err(rusty_jsc::JSException::type_error(
+                    context,
+                    concat!("Missing property `", #name, "`"),
+                ))
+            },
+        };
+        reads.push(quote! {
+            let __value = __object.get_property(context, #name);
+            let #local: #ty = if __value.is_undefined(context) {
+                #missing
+            } else {
+                <#ty as rusty_jsc::FromJs>::from_js(context, &__value)?
+            };
+        });
+    }
+    Ok(quote! {
+        #(#reads)*
+        Ok(#constructor { #(#inits),* })
+    })
+}
+
+/// Writes the named fields bound by `field_bindings` to `__object`.
+fn write_fields(fields: &[&Field], rule: RenameRule) -> syn::Result<TokenStream> {
+    let mut writes = Vec::new();
+    for field in fields {
+        let attrs = JsAttrs::parse(&field.attrs, Target::Field, &["rename", "default", "skip"])?;
+        if attrs.flag("skip") {
+            continue;
+        }
+        let ident = field.ident.as_ref().unwrap();
+        let name = js_name(&attrs, ident, rule);
+        let local = field_local(ident);
+        writes.push(quote! {
+            __object.set_property(context, #name, rusty_jsc::IntoJs::into_js(#local, context)?)?;
+        });
+    }
+    Ok(quote! { #(#writes)* })
+}
+
+/// The local a field is bound to in generated code. The prefix keeps fields
+/// named like the generated bindings (`context`, `value`, ...) from shadowing
+/// them.
+fn field_local(ident: &Ident) -> Ident {
+    format_ident!("__field_{}", ident)
+}
+
+/// The `field: local` patterns destructuring the named fields.
+fn field_bindings(fields: &[&Field]) -> Vec<TokenStream> {
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let local = field_local(ident);
+            quote! { #ident: #local }
+        })
+        .collect()
+}
+
+fn named_fields(fields: &Fields) -> Option<Vec<&Field>> {
+    match fields {
+        Fields::Named(fields) => Some(fields.named.iter().collect()),
+        _ => None,
+    }
+}
+
+/// How an enum is represented: unit variants as strings, or every variant as
+/// an object whose `tag` property holds the variant name.
+fn enum_tag(attrs: &JsAttrs, data: &DataEnum, input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let tag = attrs.string("tag");
+    for variant in &data.variants {
+        match &variant.fields {
+            Fields::Unit => {}
+            Fields::Named(_) if tag.is_some() => {}
+            Fields::Named(_) => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "enums with fields need #[js(tag = \"...\")]",
+                ))
+            }
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new(
+                    variant.span(),
+                    "tuple variants can't be converted, use named fields",
+                ))
+            }
+        }
+    }
+    Ok(tag)
+}
+
+/// Returns the name of a variant and the rule for the names of its fields.
+/// Like serde, `rename_all` on the enum renames the variants, while
+/// `rename_all` on a variant renames its fields.
+fn variant_names(variant: &Variant, rule: RenameRule) -> syn::Result<(LitStr, RenameRule)> {
+    let attrs = JsAttrs::parse(&variant.attrs, Target::Variant, &["rename", "rename_all"])?;
+    if matches!(variant.fields, Fields::Unit) {
+        attrs.reject("rename_all", "`rename_all` has no effect on a unit variant")?;
+    }
+    Ok((js_name(&attrs, &variant.ident, rule), RenameRule::parse(&attrs)?))
+}
+
+/// Rejects the options of the type that have no effect on a struct.
+fn check_struct_attrs(attrs: &JsAttrs, fields: &Fields) -> syn::Result<()> {
+    attrs.reject("tag", "`tag` only applies to enums")?;
+    if !matches!(fields, Fields::Named(_)) {
+        attrs.reject("rename_all", "`rename_all` only applies to structs with named fields")?;
+    }
+    Ok(())
+}
+
+pub(crate) fn derive_from_js(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let attrs = JsAttrs::parse(&input.attrs, Target::Item, &["rename_all", "tag"])?;
+    let rule = RenameRule::parse(&attrs)?;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(rusty_jsc::FromJs));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if let Data::Struct(data) = &input.data {
+        check_struct_attrs(&attrs, &data.fields)?;
+    }
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields).unwrap();
+                let read = read_fields(&fields, rule, quote! { #ident })?;
+                quote! {
+                    if !value.is_object(context) {
+                        return Err(rusty_jsc::JSException::type_error(
+                            context,
+                            concat!("Expected an object for ", stringify!(#ident)),
+                        ));
+                    }
+                    let __object = value.to_object(context)?;
+                    #read
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                quote! { Ok(#ident(<#ty as rusty_jsc::FromJs>::from_js(context, value)?)) }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "FromJs can only be derived for structs with named fields or a single field",
+                ))
+            }
+        },
+        Data::Enum(data) => match enum_tag(&attrs, data, &input)? {
+            None => {
+                let arms = data
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let variant_ident = &variant.ident;
+                        let (name, _) = variant_names(variant, rule)?;
+                        Ok(quote! { #name => Ok(#ident::#variant_ident), })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote! {
+                    if !value.is_string(context) {
+                        return Err(rusty_jsc::JSException::type_error(
+                            context,
+                            concat!("Expected a string for ", stringify!(#ident)),
+                        ));
+                    }
+                    let __name = value.to_string(context)?.to_string();
+                    match __name.as_str() {
+                        #(#arms)*
+                        _ => Err(rusty_jsc::JSException::type_error(
+                            context,
+                            format!("Unknown {} `{}`", stringify!(#ident), __name),
+                        )),
+                    }
+                }
+            }
+            Some(tag) => {
+                let arms = data
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let variant_ident = &variant.ident;
+                        let (name, field_rule) = variant_names(variant, rule)?;
+                        let fields = named_fields(&variant.fields).unwrap_or_default();
+                        let read = read_fields(&fields, field_rule, quote! { #ident::#variant_ident })?;
+                        Ok(quote! { #name => { #read } })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote! {
+                    if !value.is_object(context) {
+                        return Err(rusty_jsc::JSException::type_error(
+                            context,
+                            concat!("Expected an object for ", stringify!(#ident)),
+                        ));
+                    }
+                    let __object = value.to_object(context)?;
+                    let __name = __object.get_property(context, #tag).to_string(context)?.to_string();
+                    match __name.as_str() {
+                        #(#arms)*
+                        _ => Err(rusty_jsc::JSException::type_error(
+                            context,
+                            format!("Unknown {} `{}`", stringify!(#ident), __name),
+                        )),
+                    }
+                }
+            }
+        },
+        Data::Union(_) => {
+            return Err(syn::Error::new(input.span(), "FromJs can't be derived for unions"))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics rusty_jsc::FromJs for #ident #ty_generics #where_clause {
+            fn from_js(
+                context: &rusty_jsc::JSContext,
+                value: &rusty_jsc::JSValue,
+            ) -> Result<Self, rusty_jsc::JSException> {
+                #body
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_into_js(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let attrs = JsAttrs::parse(&input.attrs, Target::Item, &["rename_all", "tag"])?;
+    let rule = RenameRule::parse(&attrs)?;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(rusty_jsc::IntoJs));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if let Data::Struct(data) = &input.data {
+        check_struct_attrs(&attrs, &data.fields)?;
+    }
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields).unwrap();
+                let bindings = field_bindings(&fields);
+                let write = write_fields(&fields, rule)?;
+                quote! {
+                    #[allow(unused_variables)]
+                    let #ident { #(#bindings),* } = self;
+                    let __object = rusty_jsc::JSObject::new(context);
+                    #write
+                    Ok(__object.into())
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                quote! { rusty_jsc::IntoJs::into_js(self.0, context) }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "IntoJs can only be derived for structs with named fields or a single field",
+                ))
+            }
+        },
+        Data::Enum(data) => {
+            let tag = enum_tag(&attrs, data, &input)?;
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let (name, field_rule) = variant_names(variant, rule)?;
+                    let tag = match &tag {
+                        Some(tag) => tag,
+                        None => {
+                            return Ok(quote! {
+                                #ident::#variant_ident => Ok(rusty_jsc::JSValue::string(context, #name)),
+                            })
+                        }
+                    };
+                    let fields = named_fields(&variant.fields).unwrap_or_default();
+                    let bindings = field_bindings(&fields);
+                    let write = write_fields(&fields, field_rule)?;
+                    Ok(quote! {
+                        #[allow(unused_variables)]
+                        #ident::#variant_ident { #(#bindings),* } => {
+                            let __object = rusty_jsc::JSObject::new(context);
+                            __object.set_property(context, #tag, rusty_jsc::JSValue::string(context, #name))?;
+                            #write
+                            Ok(__object.into())
+                        }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(input.span(), "IntoJs can't be derived for unions"))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics rusty_jsc::IntoJs for #ident #ty_generics #where_clause {
+            fn into_js(
+                self,
+                context: &rusty_jsc::JSContext,
+            ) -> Result<rusty_jsc::JSValue, rusty_jsc::JSException> {
+                #body
+            }
+        }
+    })
+}
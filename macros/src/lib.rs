@@ -1,67 +1,97 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{FnArg, Ident, Pat};
+use quote::{format_ident, quote};
 
-fn get_name(func_argument: &FnArg) -> Ident {
-    match func_argument {
-        FnArg::Typed(fn_type) => get_name_pat(&*fn_type.pat),
-        _ => {
-            panic!("Not supported function argument")
-        }
-    }
-}
+mod attrs;
+mod callback;
+mod js_class;
+mod js_convert;
 
-fn get_name_pat(func_argument: &Pat) -> Ident {
-    match func_argument {
-        Pat::Ident(ident) => return ident.ident.clone(),
-        Pat::Type(pat_type) => return get_name_pat(&*pat_type.pat),
-        _ => {
-            panic!("Not supported function argument")
-        }
-    }
+/// Turns a function into a `JSObjectCallAsFunctionCallback` for
+/// `JSValue::callback`.
+///
+/// Each parameter is given by its type: a `JSContext` or `&JSContext` receives
+/// the calling context, `&[JSValue]` the arguments, and a `JSObject` the
+/// function itself or, when named `this`, the receiver. Two unnamed objects
+/// are the function and `this`, in that order. A `&T` or `&mut T` marked with
+/// `#[state]` borrows the state registered with `JSContext::set_user_data`
+/// while the function runs, and the callback throws if there is none or it is
+/// already borrowed.
+///
+/// A `rusty_jsc::This<T>`, or a `&T` named `this`, borrows the `T` held by the
+/// receiver, and the callback throws an "Illegal invocation" `TypeError` when
+/// it is invoked on an object that doesn't hold one.
+///
+/// A `rusty_jsc::NewTarget` tells whether the function was invoked with `new`.
+///
+/// Any other parameter takes the next JavaScript argument, converted with
+/// `rusty_jsc::FromJs`. Missing arguments throw a `TypeError`, unless the
+/// parameter is an `Option`, which is `None` for `undefined` or a missing
+/// argument, or has a `#[default = expr]` used in that case. A last
+/// `rusty_jsc::Rest<T>` or `Vec<T>` collects the remaining arguments; an array
+/// argument in that position can be taken as an `Option<Vec<T>>`.
+///
+/// The return value is converted with `rusty_jsc::IntoJs` and may be wrapped
+/// in a `Result<_, JSException>`.
+///
+/// `#[callback(name = "readFile", length = 2)]` sets the `name` and `length`
+/// of the function in JavaScript. They default to the Rust name and the number
+/// of arguments before the first optional one. A callback `foo` provides them
+/// as the constants `FOO_NAME` and `FOO_LENGTH` for `JSValue::callback_named`,
+/// declared next to it, in an impl block too.
+#[proc_macro_attribute]
+pub fn callback(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let func = syn::parse_macro_input!(item as syn::ItemFn);
+    callback::callback(args, func)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
+/// Turns `fn(ctx: JSContext, constructor: JSObject, args: &[JSValue]) -> Result<T, JSException>`
+/// into a `JSObjectCallAsConstructorCallback` for `JSObject::new_constructor`. The returned `T`
+/// becomes the Rust data of the new object.
 #[proc_macro_attribute]
-pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn constructor(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let func = syn::parse::<syn::ItemFn>(item).expect("expected a function");
     let name = &func.sig.ident;
-    let all_inputs = func.sig.inputs.iter().collect::<Vec<_>>();
-    assert_eq!(all_inputs.len(), 4);
-    let context_var_name = get_name(all_inputs.get(0).unwrap());
-    let function_var_name = get_name(all_inputs.get(1).unwrap());
-    let this_var_name = get_name(all_inputs.get(2).unwrap());
-    let args_var_name = get_name(all_inputs.get(3).unwrap());
+    assert_eq!(func.sig.inputs.len(), 3);
+
+    // The function is kept as is, nested in the trampoline, so that `?` and `return` work in its body.
+    let mut inner = func.clone();
+    inner.sig.ident = format_ident!("__constructor_fn");
+    inner.vis = syn::Visibility::Inherited;
+    inner.attrs = vec![];
+    let (_, ty_generics, _) = func.sig.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
 
-    let block = &func.block;
     let attrs = func.attrs;
     let result = quote! {
         unsafe extern "C" fn #name(
             __base_ctx: rusty_jsc::private::JSContextRef,
-            __function: rusty_jsc::private::JSObjectRef,
-            __this_object: rusty_jsc::private::JSObjectRef,
+            __constructor: rusty_jsc::private::JSObjectRef,
             __argument_count: rusty_jsc::private::size_t,
             __arguments: *const rusty_jsc::private::JSValueRef,
-            mut __exception: *mut rusty_jsc::private::JSValueRef,
-        ) -> rusty_jsc::private::JSValueRef {
-            let #context_var_name = rusty_jsc::JSContext::from(__base_ctx);
-            let #function_var_name: rusty_jsc::JSObject= __function.into();
-            let #this_var_name: rusty_jsc::JSObject = __this_object.into();
-            let #args_var_name = if __argument_count == 0 {
+            __exception: *mut rusty_jsc::private::JSValueRef,
+        ) -> rusty_jsc::private::JSObjectRef {
+            #inner
+
+            let __ctx = rusty_jsc::JSContext::from(__base_ctx);
+            let __constructor_object: rusty_jsc::JSObject = __constructor.into();
+            let __args = if __argument_count == 0 {
                 vec![]
             }
             else {
                 let __args_refs_slice = unsafe { std::slice::from_raw_parts(__arguments, __argument_count as _) };
-                __args_refs_slice.iter().map(|r| (*r).into()).collect::<Vec<_>>()
+                __args_refs_slice.iter().map(|r| (*r).into()).collect::<Vec<rusty_jsc::JSValue>>()
             };
-            let #args_var_name: &[JSValue] = &#args_var_name;
 
-            let res: Result<JSValue, JSException> = #block;
+            let res = __constructor_fn #turbofish(__ctx.clone(), __constructor_object.clone(), &__args)
+                .and_then(|data| rusty_jsc::JSObject::new_instance(&__ctx, &__constructor_object, data));
             match res {
-                Ok(res) => res.into(),
+                Ok(object) => object.into(),
                 Err(err) => {
-                    let ctx2 = rusty_jsc::JSContext::from(__base_ctx);
-                    *__exception = err.to_jsvalue(&ctx2).into();
-                    rusty_jsc::JSValue::undefined(&ctx2).into()
+                    *__exception = err.to_jsvalue(&__ctx).into();
+                    std::ptr::null_mut()
                 }
             }
         }
@@ -69,15 +99,83 @@ pub fn callback(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let new_func = result.into();
     // We do this so we make sure the function generics and other properties are preserved
     let mut new_func = syn::parse::<syn::ItemFn>(new_func).expect("expected a function");
-    new_func.attrs = attrs.clone();
+    new_func.attrs = attrs;
     new_func.vis = func.vis;
     new_func.sig.generics = func.sig.generics;
-    new_func.sig.constness = func.sig.constness;
-    new_func.sig.variadic = func.sig.variadic;
-    new_func.sig.asyncness = func.sig.asyncness;
 
     let result2 = quote! {
         #new_func
     };
     result2.into()
 }
+
+/// Exposes a struct to JavaScript as a `rusty_jsc::JsClass`.
+///
+/// Fields marked `#[js(get)]` become read-only properties and fields marked
+/// `#[js(get, set)]` become writable ones; a writable field needs interior
+/// mutability, such as a `Cell` or a `RefCell`. Reading a `RefCell` property
+/// while Rust code borrows it mutably throws an error. `#[js(name = "...")]`
+/// renames the class or a property. The methods come from `#[js_methods]`.
+///
+/// `#[js(extends = "Base")]` makes the class inherit from the class of
+/// `Base`, another `JsClass`. The struct then holds its `Base` value in a field
+/// marked `#[js(parent)]`, which the methods of `Base` get as their `&self`.
+///
+/// `#[js(convert)]` and `#[js(has_instance)]` on the struct hook its
+/// `rusty_jsc::JsConvert` and `rusty_jsc::JsHasInstance` implementations into
+/// the class.
+#[proc_macro_derive(JsClass, attributes(js))]
+pub fn derive_js_class(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    js_class::derive_js_class(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Exposes the `&self` methods of an `impl` block as the methods of a
+/// `#[derive(JsClass)]` type.
+///
+/// Arguments are converted with `rusty_jsc::FromJs`, except for a
+/// `&JSContext`, which receives the calling context. The return value is
+/// converted with `rusty_jsc::IntoJs` and may be wrapped in a
+/// `Result<_, JSException>`. `#[js(name = "...")]` renames a method and
+/// `#[js(skip)]` hides it.
+#[proc_macro_attribute]
+pub fn js_methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemImpl);
+    js_class::js_methods(item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `rusty_jsc::FromJs`, reading a struct from the properties of a
+/// JavaScript object.
+///
+/// Missing properties are an error unless the field is an `Option` or has
+/// `#[js(default)]` or `#[js(default = "path::to::fn")]`. Fields can be
+/// renamed with `#[js(rename = "...")]` or all at once with
+/// `#[js(rename_all = "camelCase")]`, and left out with `#[js(skip)]`.
+///
+/// Enums with only unit variants are read from strings. Enums with fields
+/// need `#[js(tag = "...")]` and are read from objects whose `tag` property
+/// names the variant. On enums, `#[js(rename_all = "...")]` renames the
+/// variants; on a variant, it renames the fields of that variant.
+///
+/// Options that have no effect where they are placed are an error.
+#[proc_macro_derive(FromJs, attributes(js))]
+pub fn derive_from_js(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    js_convert::derive_from_js(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `rusty_jsc::IntoJs`, writing a struct to the properties of a new
+/// JavaScript object. Takes the same attributes as `#[derive(FromJs)]`.
+#[proc_macro_derive(IntoJs, attributes(js))]
+pub fn derive_into_js(item: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(item as syn::DeriveInput);
+    js_convert::derive_into_js(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,131 @@
+use rusty_jsc_sys::*;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use crate::js_class::{JSClass, SharedClass};
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::macro_support::{arguments, throw};
+use crate::JSException;
+
+/// The Rust closure behind a function created with
+/// `JSObject::new_function_with_closure`.
+pub(crate) type Closure = dyn Fn(&JSContext, &JSObject, &[JSValue]) -> Result<JSValue, JSException>;
+
+static FUNCTION_CLASS: OnceLock<SharedClass> = OnceLock::new();
+
+unsafe extern "C" fn call_closure(
+    ctx: JSContextRef,
+    function: JSObjectRef,
+    this: JSObjectRef,
+    argument_count: size_t,
+    argument_refs: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let context = JSContext::from(ctx);
+    let function: JSObject = function.into();
+    // Cloned so that the closure stays alive even if it drops the function.
+    let closure = match function.downcast_ref::<Rc<Closure>>(&context) {
+        Some(closure) => closure.clone(),
+        None => return JSValue::undefined(&context).into(),
+    };
+    let this = if this.is_null() {
+        context.get_global_object()
+    } else {
+        this.into()
+    };
+    let args = arguments(argument_count, argument_refs);
+    match closure(&context, &this, &args) {
+        Ok(value) => value.into(),
+        Err(err) => {
+            throw(&context, exception, err);
+            JSValue::undefined(&context).into()
+        }
+    }
+}
+
+/// Creates a function object calling `closure`, named `name` with a `length`
+/// of `length`. The closure is dropped when the function is garbage collected.
+pub(crate) fn make_function(context: &JSContext, name: &str, length: u32, closure: Rc<Closure>) -> JSObject {
+    let class = FUNCTION_CLASS.get_or_init(|| {
+        let class = JSClass::builder("Function")
+            .call_as_function(Some(call_closure))
+            .build();
+        // Kept for the lifetime of the process, like the root class.
+        SharedClass(unsafe { JSClassRetain(*class.inner) })
+    });
+    let data: Box<Box<dyn std::any::Any>> = Box::new(Box::new(closure));
+    let function: JSObject =
+        unsafe { JSObjectMake(context.inner(), class.0, Box::into_raw(data) as _) }.into();
+    set_function_metadata(context, &function, name, length);
+    function
+}
+
+/// Makes `function`, an object of a class with a `call_as_function` callback,
+/// behave like a native function: it inherits `call`, `apply` and `bind` from
+/// `Function.prototype`, and carries a `name` and a `length`.
+pub(crate) fn set_function_metadata(context: &JSContext, function: &JSObject, name: &str, length: u32) {
+    let function_prototype = context.state.intrinsics().function_prototype();
+    unsafe { JSObjectSetPrototype(context.inner(), function.clone().into(), function_prototype.into()) };
+    let read_only = kJSPropertyAttributeReadOnly | kJSPropertyAttributeDontEnum;
+    // Setting properties of a new object doesn't throw.
+    let _ = function.set_property_with_attributes(context, "name", JSValue::string(context, name), read_only);
+    let _ = function.set_property_with_attributes(
+        context,
+        "length",
+        JSValue::number(context, length as f64),
+        read_only,
+    );
+}
+
+/// Wraps an accessor getter into a function closure.
+pub(crate) fn getter_closure<G>(getter: G) -> Rc<Closure>
+where
+    G: Fn(&JSContext, &JSObject) -> Result<JSValue, JSException> + 'static,
+{
+    Rc::new(move |context: &JSContext, this: &JSObject, _: &[JSValue]| getter(context, this))
+}
+
+/// Wraps an accessor setter into a function closure.
+pub(crate) fn setter_closure<S>(setter: S) -> Rc<Closure>
+where
+    S: Fn(&JSContext, &JSObject, JSValue) -> Result<(), JSException> + 'static,
+{
+    Rc::new(move |context: &JSContext, this: &JSObject, args: &[JSValue]| {
+        let value = args.first().cloned().unwrap_or_else(|| JSValue::undefined(context));
+        setter(context, this, value)?;
+        Ok(JSValue::undefined(context))
+    })
+}
+
+/// Defines an accessor property on `object` with `Object.defineProperty`, as
+/// the C API can't. Both the function and the descriptor are out of reach of
+/// scripts: the function is the one the context started with, and the
+/// descriptor doesn't inherit from `Object.prototype`.
+pub(crate) fn define_accessor(
+    context: &JSContext,
+    object: &JSObject,
+    name: &str,
+    getter: Option<JSObject>,
+    setter: Option<JSObject>,
+    attributes: JSPropertyAttributes,
+) -> Result<(), JSException> {
+    let descriptor = JSObject::new(context);
+    unsafe { JSObjectSetPrototype(context.inner(), descriptor.clone().into(), JSValueMakeNull(context.inner())) };
+    let undefined = || JSValue::undefined(context);
+    descriptor.set_property(context, "get", getter.map_or_else(undefined, |getter| getter.into()))?;
+    descriptor.set_property(context, "set", setter.map_or_else(undefined, |setter| setter.into()))?;
+    let enumerable = attributes & kJSPropertyAttributeDontEnum == 0;
+    let configurable = attributes & kJSPropertyAttributeDontDelete == 0;
+    descriptor.set_property(context, "enumerable", JSValue::boolean(context, enumerable))?;
+    descriptor.set_property(context, "configurable", JSValue::boolean(context, configurable))?;
+
+    let define_property = context.state.intrinsics().define_property()?.to_object(context)?;
+    define_property.call(
+        context,
+        None,
+        &[object.to_jsvalue(), JSValue::string(context, name), descriptor.into()],
+    )?;
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::Display;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::JSContext;
+
+type Task = Box<dyn FnOnce(&JSContext) + Send>;
+
+/// Error returned when posting to a context that no longer exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextClosed;
+
+impl Error for ContextClosed {}
+
+impl Display for ContextClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the JavaScript context has been dropped")
+    }
+}
+
+#[derive(Default)]
+struct TaskQueueState {
+    tasks: VecDeque<Task>,
+    closed: bool,
+}
+
+/// Tasks posted to a context from other threads, waiting to be run on the
+/// context's own thread.
+#[derive(Default)]
+pub(crate) struct TaskQueue {
+    state: Mutex<TaskQueueState>,
+    available: Condvar,
+}
+
+impl TaskQueue {
+    fn push(&self, task: Task) -> Result<(), ContextClosed> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return Err(ContextClosed);
+        }
+        state.tasks.push_back(task);
+        self.available.notify_all();
+        Ok(())
+    }
+
+    /// Takes every task posted so far.
+    pub(crate) fn take(&self) -> VecDeque<Task> {
+        std::mem::take(&mut self.state.lock().unwrap().tasks)
+    }
+
+    /// Blocks until there is at least one task, the queue is closed or the
+    /// timeout expires. Returns `true` if there are tasks to run.
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> bool {
+        let state = self.state.lock().unwrap();
+        let ready = |state: &mut TaskQueueState| state.tasks.is_empty() && !state.closed;
+        let state = match timeout {
+            Some(timeout) => self.available.wait_timeout_while(state, timeout, ready).unwrap().0,
+            None => self.available.wait_while(state, ready).unwrap(),
+        };
+        !state.tasks.is_empty()
+    }
+
+    /// Rejects new tasks and drops the pending ones.
+    pub(crate) fn close(&self) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.tasks)
+        };
+        self.available.notify_all();
+        drop(pending);
+    }
+
+    fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+}
+
+/// A thread-safe handle to a `JSContext`.
+///
+/// `JSContext`, `JSValue` and `JSObject` can only be used on the thread that
+/// owns the context. A `ContextHandle` can be sent to other threads and used to
+/// post closures that the owning thread runs with the context the next time it
+/// calls `JSContext::run_pending_tasks`.
+#[derive(Clone)]
+pub struct ContextHandle {
+    queue: Arc<TaskQueue>,
+}
+
+impl ContextHandle {
+    pub(crate) fn new(queue: Arc<TaskQueue>) -> Self {
+        ContextHandle { queue }
+    }
+
+    /// Posts `task` to be run on the context's thread.
+    ///
+    /// Returns an error if the context has already been dropped.
+    pub fn post<F>(&self, task: F) -> Result<(), ContextClosed>
+    where
+        F: FnOnce(&JSContext) + Send + 'static,
+    {
+        self.queue.push(Box::new(task))
+    }
+
+    /// Returns `true` if the context has been dropped and no longer accepts
+    /// tasks.
+    pub fn is_closed(&self) -> bool {
+        self.queue.is_closed()
+    }
+}
+
+impl std::fmt::Debug for ContextHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextHandle")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
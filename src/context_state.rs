@@ -0,0 +1,191 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::thread::LocalKey;
+
+use std::os::raw::c_void;
+
+use rusty_jsc_sys::{
+    JSContextGroupRef, JSGlobalContextRef, JSObjectRef, JSValueProtect, JSValueUnprotect, JSWeakObjectMapCreate,
+    JSWeakObjectMapRef,
+};
+
+use crate::context_handle::TaskQueue;
+use crate::intrinsics::Intrinsics;
+use crate::js_class::JSClass;
+use crate::js_object::JSObject;
+use crate::js_termination::{ExecutionLimits, HeapLimit};
+
+type Registry<T> = RefCell<HashMap<usize, Weak<T>>>;
+
+/// The data attached to a context with `JSContext::set_user_data`. Each value
+/// is an `Rc<RefCell<T>>` keyed by the `TypeId` of `T`.
+pub(crate) type UserData = RefCell<HashMap<TypeId, Rc<dyn Any>>>;
+
+thread_local! {
+    // Every `JSContext` wrapping the same global context, including the ones
+    // rebuilt from a raw `JSContextRef` inside callbacks, shares one state.
+    // The same goes for every `JSContextGroup` wrapping the same group.
+    static CONTEXT_STATES: Registry<ContextState> = RefCell::new(HashMap::new());
+    static GROUP_STATES: Registry<GroupState> = RefCell::new(HashMap::new());
+    // The user data of each global context, for as long as its global object
+    // exists.
+    static USER_DATA: RefCell<HashMap<usize, Rc<UserData>>> = RefCell::new(HashMap::new());
+}
+
+/// Returns the user data of `context`, creating it on first use. Scripts can't
+/// see it: a weak object map, which JavaScriptCore destroys along with the
+/// global object, drops it.
+pub(crate) fn user_data(context: JSGlobalContextRef) -> Rc<UserData> {
+    let key = context as usize;
+    if let Some(data) = USER_DATA.with(|all| all.borrow().get(&key).cloned()) {
+        return data;
+    }
+    let data = Rc::new(UserData::default());
+    USER_DATA.with(|all| all.borrow_mut().insert(key, data.clone()));
+    unsafe { JSWeakObjectMapCreate(context, key as *mut c_void, Some(drop_user_data)) };
+    data
+}
+
+unsafe extern "C" fn drop_user_data(_map: JSWeakObjectMapRef, key: *mut c_void) {
+    // The registry may already be gone if the thread is exiting. The data is
+    // dropped after the registry is released, in case it uses another context.
+    let data = USER_DATA
+        .try_with(|all| all.borrow_mut().remove(&(key as usize)))
+        .ok()
+        .flatten();
+    drop(data);
+}
+
+fn get_or_create<T>(registry: &'static LocalKey<Registry<T>>, key: usize, create: impl FnOnce() -> T) -> Rc<T> {
+    registry.with(|states| {
+        let mut states = states.borrow_mut();
+        if let Some(state) = states.get(&key).and_then(Weak::upgrade) {
+            return state;
+        }
+        let state = Rc::new(create());
+        states.insert(key, Rc::downgrade(&state));
+        state
+    })
+}
+
+fn lookup<T>(registry: &'static LocalKey<Registry<T>>, key: usize) -> Option<Rc<T>> {
+    registry
+        .try_with(|states| states.borrow().get(&key).and_then(Weak::upgrade))
+        .ok()
+        .flatten()
+}
+
+fn remove<T>(registry: &'static LocalKey<Registry<T>>, key: usize) {
+    // The registry may already be gone if the thread is exiting.
+    let _ = registry.try_with(|states| {
+        let mut states = states.borrow_mut();
+        if states.get(&key).is_some_and(|state| state.strong_count() == 0) {
+            states.remove(&key);
+        }
+    });
+}
+
+/// Rust-side state attached to a global context. It lives for as long as any
+/// `JSContext` wrapper for that global context is alive.
+pub(crate) struct ContextState {
+    key: usize,
+    pub(crate) tasks: Arc<TaskQueue>,
+    pub(crate) execution: ExecutionLimits,
+    intrinsics: RefCell<Option<Rc<Intrinsics>>>,
+    // The prototype each class got its closure members on. JavaScriptCore
+    // only caches the prototype of a class while it is in use, so it is
+    // protected to keep the members; the classes are kept alive so their
+    // addresses are not reused.
+    installed_classes: RefCell<HashMap<usize, (JSClass, JSObjectRef)>>,
+}
+
+impl ContextState {
+    /// Returns the state of `context`, creating it on first use.
+    pub(crate) fn for_context(context: JSGlobalContextRef) -> Rc<ContextState> {
+        let key = context as usize;
+        get_or_create(&CONTEXT_STATES, key, || ContextState {
+            key,
+            tasks: Arc::new(TaskQueue::default()),
+            execution: ExecutionLimits::default(),
+            intrinsics: RefCell::new(None),
+            installed_classes: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the built-in functions of the context, looking them up on first
+    /// use.
+    pub(crate) fn intrinsics(&self) -> Rc<Intrinsics> {
+        if let Some(intrinsics) = self.intrinsics.borrow().as_ref() {
+            return intrinsics.clone();
+        }
+        // The lookup can run callbacks of the global object, which may need
+        // the intrinsics themselves: the first capture to finish wins.
+        let intrinsics = Rc::new(Intrinsics::capture(self.key as JSGlobalContextRef));
+        self.intrinsics.borrow_mut().get_or_insert(intrinsics).clone()
+    }
+
+    /// Records that the closure members of `class` were defined on
+    /// `prototype`, and keeps it alive. Returns `false` if they already were.
+    pub(crate) fn mark_class_installed(&self, class: &JSClass, prototype: &JSObject) -> bool {
+        let context = self.key as JSGlobalContextRef;
+        let prototype: JSObjectRef = prototype.clone().into();
+        let mut installed = self.installed_classes.borrow_mut();
+        let key = *class.inner as usize;
+        if installed.get(&key).is_some_and(|(_, installed)| *installed == prototype) {
+            return false;
+        }
+        unsafe { JSValueProtect(context, prototype) };
+        if let Some((_, previous)) = installed.insert(key, (class.clone(), prototype)) {
+            unsafe { JSValueUnprotect(context, previous) };
+        }
+        true
+    }
+
+    /// Returns the state of `context` if it has one.
+    pub(crate) fn lookup(context: JSGlobalContextRef) -> Option<Rc<ContextState>> {
+        lookup(&CONTEXT_STATES, context as usize)
+    }
+}
+
+impl Drop for ContextState {
+    fn drop(&mut self) {
+        let context = self.key as JSGlobalContextRef;
+        for (_, prototype) in self.installed_classes.get_mut().values() {
+            unsafe { JSValueUnprotect(context, *prototype) };
+        }
+        self.tasks.close();
+        remove(&CONTEXT_STATES, self.key);
+    }
+}
+
+/// Rust-side state attached to a context group. It lives for as long as any
+/// `JSContextGroup` wrapper for that group is alive.
+pub(crate) struct GroupState {
+    key: usize,
+    pub(crate) heap_limit: HeapLimit,
+}
+
+impl GroupState {
+    /// Returns the state of `group`, creating it on first use.
+    pub(crate) fn for_group(group: JSContextGroupRef) -> Rc<GroupState> {
+        let key = group as usize;
+        get_or_create(&GROUP_STATES, key, || GroupState {
+            key,
+            heap_limit: HeapLimit::default(),
+        })
+    }
+
+    /// Returns the state of `group` if it has one.
+    pub(crate) fn lookup(group: JSContextGroupRef) -> Option<Rc<GroupState>> {
+        lookup(&GROUP_STATES, group as usize)
+    }
+}
+
+impl Drop for GroupState {
+    fn drop(&mut self) {
+        remove(&GROUP_STATES, self.key);
+    }
+}
@@ -0,0 +1,106 @@
+use std::ptr;
+
+use rusty_jsc_sys::*;
+
+use crate::internal::JSString;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// Built-in objects that the helpers of this crate use.
+///
+/// They are looked up once per global context, right after `JSContext`
+/// creates it, so that scripts replacing `Object.defineProperty` or
+/// `Reflect.construct` later on can't intercept the objects the helpers work
+/// with. Contexts created outside of this crate look them up on first use.
+/// They are protected from garbage collection until the last `JSContext`
+/// wrapper is gone.
+pub(crate) struct Intrinsics {
+    context: JSGlobalContextRef,
+    construct: JSObjectRef,
+    set_prototype_of: JSObjectRef,
+    define_property: JSObjectRef,
+    function_prototype: JSValueRef,
+}
+
+impl Intrinsics {
+    pub(crate) fn capture(context: JSGlobalContextRef) -> Self {
+        Intrinsics {
+            context,
+            construct: capture(context, "Reflect", "construct"),
+            set_prototype_of: capture(context, "Object", "setPrototypeOf"),
+            define_property: capture(context, "Object", "defineProperty"),
+            function_prototype: capture_function_prototype(context),
+        }
+    }
+
+    /// `Reflect.construct`.
+    pub(crate) fn construct(&self) -> Result<JSValue, JSException> {
+        value(self.construct, "Reflect.construct")
+    }
+
+    /// `Object.setPrototypeOf`.
+    pub(crate) fn set_prototype_of(&self) -> Result<JSValue, JSException> {
+        value(self.set_prototype_of, "Object.setPrototypeOf")
+    }
+
+    /// `Object.defineProperty`.
+    pub(crate) fn define_property(&self) -> Result<JSValue, JSException> {
+        value(self.define_property, "Object.defineProperty")
+    }
+
+    /// `Function.prototype`.
+    pub(crate) fn function_prototype(&self) -> JSValue {
+        JSValue::from(self.function_prototype)
+    }
+}
+
+impl Drop for Intrinsics {
+    fn drop(&mut self) {
+        for function in [self.construct, self.set_prototype_of, self.define_property] {
+            if !function.is_null() {
+                unsafe { JSValueUnprotect(self.context, function) };
+            }
+        }
+        unsafe { JSValueUnprotect(self.context, self.function_prototype) };
+    }
+}
+
+fn value(function: JSObjectRef, name: &str) -> Result<JSValue, JSException> {
+    if function.is_null() {
+        return Err(JSException::from(format!("`{}` was not available when the context was created", name).as_str()));
+    }
+    Ok(JSValue::from(function as JSValueRef))
+}
+
+/// Reads `namespace.name` from the global object, returning null unless it is
+/// a function.
+fn capture(context: JSGlobalContextRef, namespace: &str, name: &str) -> JSObjectRef {
+    let get = |object: JSObjectRef, name: &str| -> JSObjectRef {
+        let name = JSString::from_utf8(name.to_string());
+        let value = unsafe { JSObjectGetProperty(context, object, name.inner, ptr::null_mut()) };
+        if value.is_null() || !unsafe { JSValueIsObject(context, value) } {
+            return ptr::null_mut();
+        }
+        unsafe { JSValueToObject(context, value, ptr::null_mut()) }
+    };
+    let global = unsafe { JSContextGetGlobalObject(context) };
+    let namespace = get(global, namespace);
+    if namespace.is_null() {
+        return ptr::null_mut();
+    }
+    let function = get(namespace, name);
+    if function.is_null() || !unsafe { JSObjectIsFunction(context, function) } {
+        return ptr::null_mut();
+    }
+    unsafe { JSValueProtect(context, function) };
+    function
+}
+
+/// Reads `Function.prototype` from a new native function, which inherits from
+/// it whatever scripts did to the global `Function`.
+fn capture_function_prototype(context: JSGlobalContextRef) -> JSValueRef {
+    let function = unsafe { JSObjectMakeFunctionWithCallback(context, ptr::null_mut(), None) };
+    let prototype = unsafe { JSObjectGetPrototype(context, function) };
+    unsafe { JSValueProtect(context, prototype) };
+    prototype
+}
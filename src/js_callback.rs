@@ -0,0 +1,192 @@
+use rusty_jsc_sys::*;
+use std::cell::RefCell;
+use std::ops::Deref;
+use std::sync::OnceLock;
+
+use crate::closure_function::set_function_metadata;
+use crate::js_class::{JSClass, SharedClass};
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+static CALLBACK_CLASS: OnceLock<SharedClass> = OnceLock::new();
+
+thread_local! {
+    /// The function and `new.target` of the running calls to functions created
+    /// with `JSValue::callback_named`, innermost last. `new.target` is null for
+    /// plain calls.
+    static INVOCATIONS: RefCell<Vec<(JSObjectRef, JSObjectRef)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// `new.target` in a callback: the constructor when the callback was invoked
+/// with `new`, and `None` when it was called as a function.
+///
+/// Only functions created with `JSValue::callback_named` can be invoked with
+/// `new`. A `#[callback]` gets it by taking a `NewTarget` parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewTarget(pub Option<JSObject>);
+
+impl NewTarget {
+    /// Returns `new.target` for the running call of `function`, the function
+    /// object a callback receives.
+    pub fn of(function: &JSObject) -> Self {
+        let function: JSObjectRef = function.clone().into();
+        let new_target = INVOCATIONS.with(|invocations| {
+            invocations
+                .borrow()
+                .last()
+                .filter(|(running, _)| *running == function)
+                .map(|(_, new_target)| *new_target)
+        });
+        NewTarget(new_target.filter(|new_target| !new_target.is_null()).map(|new_target| new_target.into()))
+    }
+
+    /// Checks if the callback was invoked with `new`.
+    pub fn is_constructor_call(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// The receiver of a `#[callback]`, checked to be an object holding a `T`, as
+/// created by `JSObject::new_with_data`, `JsClass::into_object` or
+/// `HostObject::into_object`. Dereferences to the `T`.
+///
+/// A `#[callback]` taking a `This<T>`, or a `&T` named `this`, throws a
+/// `TypeError` when invoked on any other receiver.
+#[derive(Debug)]
+pub struct This<'a, T> {
+    object: &'a JSObject,
+    value: &'a T,
+}
+
+impl<'a, T: 'static> This<'a, T> {
+    /// Checks that `object` holds a `T`, failing with an "Illegal invocation"
+    /// `TypeError` otherwise.
+    pub fn new(context: &JSContext, object: &'a JSObject) -> Result<Self, JSException> {
+        let raw: JSObjectRef = object.clone().into();
+        // Functions called without a receiver may get a null `this`.
+        let value = if raw.is_null() { None } else { object.downcast_ref::<T>(context) };
+        match value {
+            Some(value) => Ok(This { object, value }),
+            None => Err(JSException::type_error(context, "Illegal invocation")),
+        }
+    }
+}
+
+impl<'a, T> This<'a, T> {
+    /// Returns the receiver as an object.
+    pub fn object(&self) -> &'a JSObject {
+        self.object
+    }
+
+    /// Returns the `T` held by the receiver.
+    pub fn into_inner(self) -> &'a T {
+        self.value
+    }
+}
+
+impl<T> Deref for This<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+unsafe fn invoke(
+    ctx: JSContextRef,
+    function: JSObjectRef,
+    new_target: JSObjectRef,
+    this: JSObjectRef,
+    argument_count: size_t,
+    arguments: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let context = JSContext::from(ctx);
+    let object: JSObject = function.into();
+    let callback = match object.downcast_ref::<JSObjectCallAsFunctionCallback>(&context) {
+        Some(Some(callback)) => *callback,
+        _ => return JSValue::undefined(&context).into(),
+    };
+    INVOCATIONS.with(|invocations| invocations.borrow_mut().push((function, new_target)));
+    let result = callback(ctx, function, this, argument_count, arguments, exception);
+    INVOCATIONS.with(|invocations| invocations.borrow_mut().pop());
+    result
+}
+
+unsafe extern "C" fn call_callback(
+    ctx: JSContextRef,
+    function: JSObjectRef,
+    this: JSObjectRef,
+    argument_count: size_t,
+    arguments: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    invoke(ctx, function, std::ptr::null_mut(), this, argument_count, arguments, exception)
+}
+
+/// Calls the callback with a new object inheriting from the `prototype` of the
+/// function, which is the result unless the callback returns another object.
+unsafe extern "C" fn construct_callback(
+    ctx: JSContextRef,
+    constructor: JSObjectRef,
+    argument_count: size_t,
+    arguments: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSObjectRef {
+    let context = JSContext::from(ctx);
+    let this = JSObject::new(&context);
+    let constructor_object: JSObject = constructor.into();
+    let prototype = constructor_object.get_property(&context, "prototype");
+    if prototype.is_object(&context) {
+        JSObjectSetPrototype(ctx, this.clone().into(), prototype.into());
+    }
+    let result = invoke(ctx, constructor, constructor, this.clone().into(), argument_count, arguments, exception);
+    if !exception.is_null() && !(*exception).is_null() {
+        return std::ptr::null_mut();
+    }
+    if !result.is_null() && JSValueIsObject(ctx, result) {
+        return result as JSObjectRef;
+    }
+    this.into()
+}
+
+/// Creates a function calling `callback`, named `name` with a `length` of
+/// `length`, that can also be invoked with `new`.
+pub(crate) fn make_callback(
+    context: &JSContext,
+    name: &str,
+    length: u32,
+    callback: JSObjectCallAsFunctionCallback,
+) -> JSObject {
+    let class = CALLBACK_CLASS.get_or_init(|| {
+        let class = JSClass::builder("Function")
+            .call_as_function(Some(call_callback))
+            .call_as_constructor(Some(construct_callback))
+            .build();
+        // Kept for the lifetime of the process, like the root class.
+        SharedClass(unsafe { JSClassRetain(*class.inner) })
+    });
+    let data: Box<Box<dyn std::any::Any>> = Box::new(Box::new(callback));
+    let function: JSObject =
+        unsafe { JSObjectMake(context.inner(), class.0, Box::into_raw(data) as _) }.into();
+
+    set_function_metadata(context, &function, name, length);
+    // Like functions declared in scripts, callbacks carry a `prototype` for
+    // the objects `new` creates.
+    let prototype = JSObject::new(context);
+    let _ = prototype.set_property_with_attributes(
+        context,
+        "constructor",
+        function.to_jsvalue(),
+        kJSPropertyAttributeDontEnum,
+    );
+    let _ = function.set_property_with_attributes(
+        context,
+        "prototype",
+        prototype.into(),
+        kJSPropertyAttributeDontEnum | kJSPropertyAttributeDontDelete,
+    );
+    function
+}
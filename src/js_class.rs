@@ -0,0 +1,674 @@
+use rusty_jsc_sys::*;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use crate::closure_function::{define_accessor, getter_closure, make_function, setter_closure, Closure};
+use crate::helpers::RetainReleaseWrapper;
+use crate::internal::JSString;
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// A class created once for the whole process and never released.
+pub(crate) struct SharedClass(pub(crate) JSClassRef);
+
+// Classes are immutable once created and JavaScriptCore reference counts them
+// atomically.
+unsafe impl Send for SharedClass {}
+unsafe impl Sync for SharedClass {}
+
+/// The class every `JSClass` inherits from. Its finalizer drops the Rust data
+/// attached to objects with `JSObject::new_with_data`.
+static ROOT_CLASS: OnceLock<SharedClass> = OnceLock::new();
+
+unsafe extern "C" fn finalize_private_data(object: JSObjectRef) {
+    let data = JSObjectGetPrivate(object) as *mut Box<dyn Any>;
+    if !data.is_null() {
+        drop(Box::from_raw(data));
+    }
+}
+
+pub(crate) fn root_class() -> JSClassRef {
+    ROOT_CLASS
+        .get_or_init(|| {
+            let mut builder = JSClassBuilder::new("RustObject");
+            builder.definition.attributes = kJSClassAttributeNoAutomaticPrototype;
+            builder.definition.finalize = Some(finalize_private_data);
+            let name = CString::new(builder.name.as_bytes()).unwrap();
+            builder.definition.className = name.as_ptr();
+            SharedClass(unsafe { JSClassCreate(&builder.definition) })
+        })
+        .0
+}
+
+/// The class of the native constructors created with
+/// `JSObject::new_constructor`. Their private data is a `ConstructorData`,
+/// which scripts have no way to forge.
+static CONSTRUCTOR_CLASS: OnceLock<SharedClass> = OnceLock::new();
+
+/// What a native constructor creates: objects of `class`, made by `callback`.
+pub(crate) struct ConstructorData {
+    pub(crate) class: JSClass,
+    pub(crate) callback: JSObjectCallAsConstructorCallback,
+}
+
+unsafe extern "C" fn finalize_constructor_data(object: JSObjectRef) {
+    let data = JSObjectGetPrivate(object) as *mut ConstructorData;
+    if !data.is_null() {
+        drop(Box::from_raw(data));
+    }
+}
+
+unsafe extern "C" fn construct(
+    ctx: JSContextRef,
+    constructor: JSObjectRef,
+    argument_count: size_t,
+    arguments: *const JSValueRef,
+    exception: *mut JSValueRef,
+) -> JSObjectRef {
+    let data = &*(JSObjectGetPrivate(constructor) as *const ConstructorData);
+    match data.callback {
+        Some(callback) => callback(ctx, constructor, argument_count, arguments, exception),
+        // What `JSObjectMakeConstructor` does without a callback.
+        None => JSObjectMake(ctx, *data.class.inner, std::ptr::null_mut()),
+    }
+}
+
+/// Native constructors are not functions, so `instanceof` is implemented here
+/// the way it is for functions: by looking for their `prototype` in the
+/// prototype chain of the value.
+unsafe extern "C" fn has_instance(
+    ctx: JSContextRef,
+    constructor: JSObjectRef,
+    possible_instance: JSValueRef,
+    exception: *mut JSValueRef,
+) -> bool {
+    let name = JSString::from_utf8("prototype".to_string());
+    let prototype = JSObjectGetProperty(ctx, constructor, name.inner, exception);
+    if !(*exception).is_null() || !JSValueIsObject(ctx, possible_instance) {
+        return false;
+    }
+    let mut object = possible_instance as JSObjectRef;
+    loop {
+        let next = JSObjectGetPrototype(ctx, object);
+        if !JSValueIsObject(ctx, next) {
+            return false;
+        }
+        if JSValueIsStrictEqual(ctx, next, prototype) {
+            return true;
+        }
+        object = next as JSObjectRef;
+    }
+}
+
+pub(crate) fn constructor_class() -> JSClassRef {
+    CONSTRUCTOR_CLASS
+        .get_or_init(|| {
+            let mut builder = JSClassBuilder::new("RustConstructor");
+            builder.definition.attributes = kJSClassAttributeNoAutomaticPrototype;
+            builder.definition.finalize = Some(finalize_constructor_data);
+            builder.definition.callAsConstructor = Some(construct);
+            builder.definition.hasInstance = Some(has_instance);
+            let name = CString::new(builder.name.as_bytes()).unwrap();
+            builder.definition.className = name.as_ptr();
+            SharedClass(unsafe { JSClassCreate(&builder.definition) })
+        })
+        .0
+}
+
+/// Returns the data of `constructor` if it was created with
+/// `JSObject::new_constructor`.
+pub(crate) fn constructor_data<'a>(context: &JSContext, constructor: &'a JSObject) -> Option<&'a ConstructorData> {
+    let object: JSObjectRef = constructor.clone().into();
+    if !unsafe { JSValueIsObjectOfClass(context.inner(), object, constructor_class()) } {
+        return None;
+    }
+    unsafe { (JSObjectGetPrivate(object) as *const ConstructorData).as_ref() }
+}
+
+/// A JavaScript class, used to create objects and global objects whose
+/// behaviour is implemented in Rust.
+///
+/// Classes are created with `JSClass::builder`, which takes the same callbacks
+/// as JavaScriptCore's `JSClassDefinition`.
+#[derive(Clone)]
+pub struct JSClass {
+    pub(crate) inner: RetainReleaseWrapper<JSClassRef>,
+    name: String,
+    members: Rc<Vec<ClosureMember>>,
+    parent: Option<Box<JSClass>>,
+}
+
+/// A member of a class implemented with Rust closures. Unlike static values and
+/// functions, closures can't go in the class definition, so they are defined on
+/// the prototype of the class the first time it is used in a context.
+enum ClosureMember {
+    Function {
+        name: String,
+        closure: Rc<Closure>,
+        attributes: JSPropertyAttributes,
+    },
+    Value {
+        name: String,
+        getter: Rc<Closure>,
+        setter: Option<Rc<Closure>>,
+        attributes: JSPropertyAttributes,
+    },
+}
+
+impl fmt::Debug for JSClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JSClass").field("name", &self.name).finish()
+    }
+}
+
+impl PartialEq for JSClass {
+    fn eq(&self, other: &Self) -> bool {
+        *self.inner == *other.inner
+    }
+}
+
+impl Eq for JSClass {}
+
+impl JSClass {
+    /// Starts the definition of a class called `name`.
+    pub fn builder(name: &str) -> JSClassBuilder {
+        JSClassBuilder::new(name)
+    }
+
+    /// Returns the name of the class.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn has_closure_members(&self) -> bool {
+        !self.members.is_empty()
+            || self.parent.as_ref().is_some_and(|parent| parent.has_closure_members())
+    }
+
+    /// Defines the closure members of the class, and of its parents, on
+    /// `prototype`, the prototype of the class in `context`. Only the first
+    /// call for a prototype does anything.
+    pub(crate) fn install_members(&self, context: &JSContext, prototype: &JSObject) {
+        if !self.has_closure_members() || !context.state.mark_class_installed(self, prototype) {
+            return;
+        }
+        for member in self.members.iter() {
+            // Defining properties on a prototype only fails if the context is
+            // being terminated, in which case nothing will use them.
+            let _ = match member {
+                ClosureMember::Function { name, closure, attributes } => {
+                    let function = make_function(context, name, 0, closure.clone());
+                    prototype.set_property_with_attributes(context, name.as_str(), function.into(), *attributes)
+                }
+                ClosureMember::Value { name, getter, setter, attributes } => {
+                    let getter = make_function(context, &format!("get {}", name), 0, getter.clone());
+                    let setter = setter
+                        .as_ref()
+                        .map(|setter| make_function(context, &format!("set {}", name), 1, setter.clone()));
+                    define_accessor(context, prototype, name, Some(getter), setter, *attributes)
+                }
+            };
+        }
+        if let Some(parent) = &self.parent {
+            if let Some(parent_prototype) = prototype.prototype(context) {
+                parent.install_members(context, &parent_prototype);
+            }
+        }
+    }
+}
+
+/// Builds a `JSClass`. Every callback is optional; the ones left unset fall
+/// back to the behaviour of a plain JavaScript object.
+///
+/// Objects of the class can carry Rust data, see `JSObject::new_with_data`,
+/// which is dropped when they are garbage collected.
+///
+/// Properties and methods shared by every object of the class are declared
+/// with the `static_value` and `static_function` methods, either from
+/// callbacks, such as `#[callback]` functions, or from closures.
+pub struct JSClassBuilder {
+    name: String,
+    definition: JSClassDefinition,
+    static_values: Vec<(CString, JSObjectGetPropertyCallback, JSObjectSetPropertyCallback, JSPropertyAttributes)>,
+    static_functions: Vec<(CString, JSObjectCallAsFunctionCallback, JSPropertyAttributes)>,
+    parent: Option<JSClass>,
+    members: Vec<ClosureMember>,
+}
+
+impl fmt::Debug for JSClassBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JSClassBuilder").field("name", &self.name).finish()
+    }
+}
+
+impl JSClassBuilder {
+    fn new(name: &str) -> Self {
+        JSClassBuilder {
+            name: name.to_string(),
+            definition: JSClassDefinition {
+                version: 0,
+                attributes: kJSClassAttributeNone,
+                className: std::ptr::null(),
+                parentClass: std::ptr::null_mut(),
+                staticValues: std::ptr::null(),
+                staticFunctions: std::ptr::null(),
+                initialize: None,
+                finalize: None,
+                hasProperty: None,
+                getProperty: None,
+                setProperty: None,
+                deleteProperty: None,
+                getPropertyNames: None,
+                callAsFunction: None,
+                callAsConstructor: None,
+                hasInstance: None,
+                convertToType: None,
+            },
+            static_values: Vec::new(),
+            static_functions: Vec::new(),
+            parent: None,
+            members: Vec::new(),
+        }
+    }
+
+    /// Makes the class inherit from `parent`: objects of the class have the
+    /// properties and methods of `parent` too, and its prototype inherits from
+    /// the prototype of `parent`, so `instanceof` works through the chain.
+    pub fn parent(mut self, parent: &JSClass) -> Self {
+        self.parent = Some(parent.clone());
+        self
+    }
+
+    /// Adds a property, present on every object of the class, that is read
+    /// with `getter` and written with `setter`. A property without a setter
+    /// is read-only.
+    pub fn static_value(
+        mut self,
+        name: &str,
+        getter: JSObjectGetPropertyCallback,
+        setter: JSObjectSetPropertyCallback,
+        attributes: JSPropertyAttributes,
+    ) -> Self {
+        let attributes = if setter.is_none() {
+            attributes | kJSPropertyAttributeReadOnly
+        } else {
+            attributes
+        };
+        let name = CString::new(name).expect("property names can't contain NUL bytes");
+        self.static_values.push((name, getter, setter, attributes));
+        self
+    }
+
+    /// Adds a method, shared by every object of the class through its
+    /// prototype.
+    pub fn static_function(
+        mut self,
+        name: &str,
+        callback: JSObjectCallAsFunctionCallback,
+        attributes: JSPropertyAttributes,
+    ) -> Self {
+        let name = CString::new(name).expect("function names can't contain NUL bytes");
+        self.static_functions.push((name, callback, attributes));
+        self
+    }
+
+    /// Adds a method implemented by `closure`, which receives the context,
+    /// `this` and the arguments. The closure lives as long as the class.
+    pub fn static_function_with_closure<F>(
+        mut self,
+        name: &str,
+        closure: F,
+        attributes: JSPropertyAttributes,
+    ) -> Self
+    where
+        F: Fn(&JSContext, &JSObject, &[JSValue]) -> Result<JSValue, JSException> + 'static,
+    {
+        self.members.push(ClosureMember::Function {
+            name: name.to_string(),
+            closure: Rc::new(closure),
+            attributes,
+        });
+        self
+    }
+
+    /// Adds a read-only property whose value is computed by `getter`, which
+    /// receives the context and `this`.
+    pub fn static_value_with_closure<G>(
+        mut self,
+        name: &str,
+        getter: G,
+        attributes: JSPropertyAttributes,
+    ) -> Self
+    where
+        G: Fn(&JSContext, &JSObject) -> Result<JSValue, JSException> + 'static,
+    {
+        self.members.push(ClosureMember::Value {
+            name: name.to_string(),
+            getter: getter_closure(getter),
+            setter: None,
+            attributes,
+        });
+        self
+    }
+
+    /// Adds a property read with `getter` and written with `setter`, which
+    /// receives the context, `this` and the assigned value.
+    pub fn static_value_with_closures<G, S>(
+        mut self,
+        name: &str,
+        getter: G,
+        setter: S,
+        attributes: JSPropertyAttributes,
+    ) -> Self
+    where
+        G: Fn(&JSContext, &JSObject) -> Result<JSValue, JSException> + 'static,
+        S: Fn(&JSContext, &JSObject, JSValue) -> Result<(), JSException> + 'static,
+    {
+        self.members.push(ClosureMember::Value {
+            name: name.to_string(),
+            getter: getter_closure(getter),
+            setter: Some(setter_closure(setter)),
+            attributes,
+        });
+        self
+    }
+
+    /// Called when an object of the class is created.
+    pub fn initialize(mut self, callback: JSObjectInitializeCallback) -> Self {
+        self.definition.initialize = callback;
+        self
+    }
+
+    /// Called to check if an object has a property. Falls back to
+    /// `get_property` when unset.
+    pub fn has_property(mut self, callback: JSObjectHasPropertyCallback) -> Self {
+        self.definition.hasProperty = callback;
+        self
+    }
+
+    /// Called when a property is read. Returning `NULL` forwards the lookup to
+    /// the object's own properties and its prototype chain.
+    pub fn get_property(mut self, callback: JSObjectGetPropertyCallback) -> Self {
+        self.definition.getProperty = callback;
+        self
+    }
+
+    /// Called when a property is written. Returning `false` lets the object
+    /// store the property itself.
+    pub fn set_property(mut self, callback: JSObjectSetPropertyCallback) -> Self {
+        self.definition.setProperty = callback;
+        self
+    }
+
+    /// Called when a property is deleted. Returning `false` lets the object
+    /// delete the property itself.
+    pub fn delete_property(mut self, callback: JSObjectDeletePropertyCallback) -> Self {
+        self.definition.deleteProperty = callback;
+        self
+    }
+
+    /// Called when the property names of an object are collected, for example
+    /// by `for...in`.
+    pub fn get_property_names(mut self, callback: JSObjectGetPropertyNamesCallback) -> Self {
+        self.definition.getPropertyNames = callback;
+        self
+    }
+
+    /// Called when an object of the class is called as a function.
+    pub fn call_as_function(mut self, callback: JSObjectCallAsFunctionCallback) -> Self {
+        self.definition.callAsFunction = callback;
+        self
+    }
+
+    /// Called when an object of the class is used as a constructor in a `new`
+    /// expression.
+    pub fn call_as_constructor(mut self, callback: JSObjectCallAsConstructorCallback) -> Self {
+        self.definition.callAsConstructor = callback;
+        self
+    }
+
+    /// Called when an object of the class is the right-hand side of an
+    /// `instanceof` expression.
+    pub fn has_instance(mut self, callback: JSObjectHasInstanceCallback) -> Self {
+        self.definition.hasInstance = callback;
+        self
+    }
+
+    /// Called when an object of the class is converted to a number or a
+    /// string.
+    pub fn convert_to_type(mut self, callback: JSObjectConvertToTypeCallback) -> Self {
+        self.definition.convertToType = callback;
+        self
+    }
+
+    /// Creates the class.
+    pub fn build(mut self) -> JSClass {
+        // JavaScriptCore copies the name, so it only needs to outlive the call
+        // to `JSClassCreate`.
+        let name = CString::new(self.name.as_bytes()).unwrap();
+        self.definition.className = name.as_ptr();
+        self.definition.parentClass = match &self.parent {
+            // JavaScriptCore does not retain the parent class, which has to live
+            // for as long as the objects of this class, so it is never released.
+            Some(parent) => unsafe { JSClassRetain(*parent.inner) },
+            None => root_class(),
+        };
+
+        // The tables are copied too. Each one ends with a zeroed entry.
+        let mut static_values = self
+            .static_values
+            .iter()
+            .map(|(name, getter, setter, attributes)| JSStaticValue {
+                name: name.as_ptr(),
+                getProperty: *getter,
+                setProperty: *setter,
+                attributes: *attributes,
+            })
+            .collect::<Vec<_>>();
+        if !static_values.is_empty() {
+            static_values.push(JSStaticValue {
+                name: std::ptr::null(),
+                getProperty: None,
+                setProperty: None,
+                attributes: 0,
+            });
+            self.definition.staticValues = static_values.as_ptr();
+        }
+        let mut static_functions = self
+            .static_functions
+            .iter()
+            .map(|(name, callback, attributes)| JSStaticFunction {
+                name: name.as_ptr(),
+                callAsFunction: *callback,
+                attributes: *attributes,
+            })
+            .collect::<Vec<_>>();
+        if !static_functions.is_empty() {
+            static_functions.push(JSStaticFunction {
+                name: std::ptr::null(),
+                callAsFunction: None,
+                attributes: 0,
+            });
+            self.definition.staticFunctions = static_functions.as_ptr();
+        }
+
+        let class = unsafe { JSClassCreate(&self.definition) };
+        let inner = RetainReleaseWrapper::<JSClassRef>::new(
+            class,
+            true,
+            |x| unsafe { JSClassRetain(x); },
+            |x| unsafe { JSClassRelease(x) }
+        );
+
+        JSClass {
+            inner,
+            name: self.name,
+            members: Rc::new(self.members),
+            parent: self.parent.map(Box::new),
+        }
+    }
+}
+
+thread_local! {
+    static RUST_CLASSES: RefCell<HashMap<TypeId, JSClass>> = RefCell::new(HashMap::new());
+}
+
+/// Borrows the value of the parent class held by a value of a class, given as
+/// a `dyn Any`.
+type Upcast = fn(&dyn Any) -> Option<&dyn Any>;
+
+thread_local! {
+    // Lets the methods of a parent class borrow their value from the objects
+    // of the classes extending it.
+    static UPCASTS: RefCell<HashMap<TypeId, Upcast>> = RefCell::new(HashMap::new());
+}
+
+fn upcast_any<T: JsClass>(data: &dyn Any) -> Option<&dyn Any> {
+    data.downcast_ref::<T>().and_then(T::upcast)
+}
+
+/// Borrows `data` as a `T`, either directly or through the values of the parent
+/// classes it holds.
+pub(crate) fn downcast_data<T: 'static>(mut data: &dyn Any) -> Option<&T> {
+    loop {
+        if let Some(data) = data.downcast_ref::<T>() {
+            return Some(data);
+        }
+        let upcast = UPCASTS.with(|upcasts| upcasts.borrow().get(&(*data).type_id()).copied())?;
+        data = upcast(data)?;
+    }
+}
+
+/// Returns the class backing the Rust type `T` on this thread, creating it
+/// with `build` on first use.
+pub(crate) fn rust_class<T: 'static>(build: impl FnOnce() -> JSClass) -> JSClass {
+    RUST_CLASSES.with(|classes| {
+        if let Some(class) = classes.borrow().get(&TypeId::of::<T>()) {
+            return class.clone();
+        }
+        // Built without holding the borrow, in case `build` needs another class.
+        let class = build();
+        classes.borrow_mut().insert(TypeId::of::<T>(), class.clone());
+        class
+    })
+}
+
+/// A Rust type exposed to JavaScript as a class, usually implemented with
+/// `#[derive(JsClass)]`.
+///
+/// The derive exposes the fields marked with `#[js(get)]` or `#[js(get, set)]`
+/// as properties, and `#[js_methods]` on an `impl` block exposes its `&self`
+/// methods. A type without methods needs an empty `impl JsMethods`.
+///
+/// ```ignore
+/// #[derive(JsClass)]
+/// struct Counter {
+///     #[js(get)]
+///     name: String,
+///     #[js(get, set)]
+///     count: Cell<u32>,
+/// }
+///
+/// #[js_methods]
+/// impl Counter {
+///     fn increment(&self, by: u32) -> u32 {
+///         self.count.set(self.count.get() + by);
+///         self.count.get()
+///     }
+/// }
+///
+/// let counter = Counter { name: "clicks".into(), count: Cell::new(0) }.into_object(&context);
+/// ```
+pub trait JsClass: JsMethods + Sized + 'static {
+    /// The name of the class in JavaScript.
+    const NAME: &'static str;
+
+    #[doc(hidden)]
+    fn define_properties(builder: JSClassBuilder) -> JSClassBuilder;
+
+    /// The class this one extends, set with `#[js(extends = "...")]`.
+    #[doc(hidden)]
+    fn parent_class() -> Option<JSClass> {
+        None
+    }
+
+    /// Borrows the value of the parent class held by this one, set with
+    /// `#[js(parent)]`.
+    #[doc(hidden)]
+    fn upcast(&self) -> Option<&dyn Any> {
+        None
+    }
+
+    /// Returns the class of the type. It is created once per thread, so every
+    /// object of the type created on a thread shares the same prototype.
+    fn class() -> JSClass {
+        rust_class::<Self>(|| {
+            let mut builder = Self::define_properties(JSClass::builder(Self::NAME));
+            if let Some(parent) = Self::parent_class() {
+                builder = builder.parent(&parent);
+                UPCASTS.with(|upcasts| {
+                    upcasts.borrow_mut().insert(TypeId::of::<Self>(), upcast_any::<Self>);
+                });
+            }
+            Self::define_methods(builder).build()
+        })
+    }
+
+    /// Moves the value into a new JavaScript object of the class. It is
+    /// dropped when the object is garbage collected.
+    fn into_object(self, context: &JSContext) -> JSObject {
+        JSObject::new_with_data(context, &Self::class(), self)
+    }
+
+    /// Borrows the value behind `this`, failing with a `TypeError` if it is
+    /// not an object of the class.
+    fn from_this<'a>(context: &JSContext, this: &'a JSObject) -> Result<&'a Self, JSException> {
+        this.downcast_ref::<Self>(context).ok_or_else(|| {
+            JSException::type_error(context, format!("Receiver is not a {}", Self::NAME))
+        })
+    }
+}
+
+/// How the objects of a `JsClass` convert to primitive values, enabled with
+/// `#[js(convert)]` on the `#[derive(JsClass)]` type.
+///
+/// A conversion left as `None` falls back to the default one, which uses the
+/// object's `valueOf` and `toString` methods.
+pub trait JsConvert: JsClass {
+    /// Converts the object to a number, for arithmetic and comparisons.
+    fn convert_to_number(&self, _context: &JSContext) -> Result<Option<f64>, JSException> {
+        Ok(None)
+    }
+
+    /// Converts the object to a string, for concatenation and templates.
+    fn convert_to_string(&self, _context: &JSContext) -> Result<Option<String>, JSException> {
+        Ok(None)
+    }
+}
+
+/// Custom `instanceof` behaviour for a `JsClass`, enabled with
+/// `#[js(has_instance)]` on the `#[derive(JsClass)]` type.
+///
+/// It applies when an object of the class is the right-hand side of
+/// `instanceof`, such as an object exposing a type to scripts. Constructors
+/// created with `JSObject::new_constructor` already recognize the objects of
+/// their class.
+pub trait JsHasInstance: JsClass {
+    /// Checks if `value` is an instance of the type represented by `self`.
+    fn has_instance(&self, context: &JSContext, value: &JSValue) -> Result<bool, JSException>;
+}
+
+/// The methods of a `JsClass`, usually implemented with `#[js_methods]`.
+pub trait JsMethods {
+    #[doc(hidden)]
+    fn define_methods(builder: JSClassBuilder) -> JSClassBuilder {
+        builder
+    }
+}
@@ -1,28 +1,43 @@
 use crate::internal::JSString;
 use rusty_jsc_sys::*;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
 
+use crate::context_handle::ContextHandle;
+use crate::context_state::{self, ContextState, UserData};
 use crate::helpers::RetainReleaseWrapper;
 
+use crate::js_class::JSClass;
+use crate::js_context_group::JSContextGroup;
+use crate::js_heap_statistics::JSHeapStatistics;
+use crate::js_termination::{install_watchdog, TerminationHandle};
 use crate::js_value::JSValue;
 use crate::js_object::JSObject;
 use crate::JSException;
 
 /// A JavaScript execution context.
+///
+/// A context can only be used on the thread that created it. Use
+/// `JSContext::handle` to interact with it from other threads.
 #[derive(Clone)]
 pub struct JSContext {
-    pub(crate) context_group: RetainReleaseWrapper<JSContextGroupRef>,
+    pub(crate) context_group: JSContextGroup,
+    // Dropped before `inner`: the state still needs the context when it goes.
+    pub(crate) state: Rc<ContextState>,
     pub(crate) inner: RetainReleaseWrapper<JSGlobalContextRef>,
 }
 
 impl fmt::Debug for JSContext {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("JSContext").finish()
+        f.debug_struct("JSContext")
+            .field("name", &self.name())
+            .finish()
     }
 }
 
-unsafe impl Send for JSContext {}
-
 impl Default for JSContext {
     fn default() -> Self {
         JSContext::new()
@@ -30,41 +45,58 @@ impl Default for JSContext {
 }
 
 impl JSContext {
-    /// Create a new `JSContext` object.
+    /// Create a new `JSContext` object in its own context group.
     pub fn new() -> Self {
-        let context_group = unsafe { JSContextGroupCreate() };
-        let inner = unsafe { JSGlobalContextCreateInGroup(context_group, std::ptr::null_mut()) };
+        Self::new_in_group(&JSContextGroup::new())
+    }
 
-        Self::new_from_raw(
-            context_group,
-            true,
-            inner,
-            true
-        )
+    /// Create a new `JSContext` object named `name` in its own context group.
+    /// The name shows up in debuggers, in `Debug` output and in the
+    /// exceptions thrown in this context.
+    pub fn with_name(name: impl Into<JSString>) -> Self {
+        let context = Self::new();
+        context.set_name(name);
+        context
     }
 
-    /// Create a new `JSContext` in the same group as `self`. These new independent context will be able
-    /// to share objects but they will have different execution paths. This enables multithreaded execution.
-    pub fn new_sibling(&self) -> Self {
-        let context_group = *self.context_group;
-        let inner = unsafe { JSGlobalContextCreateInGroup(context_group, std::ptr::null_mut()) };
+    /// Create a new `JSContext` object in its own context group, whose global
+    /// object is an instance of `class`.
+    ///
+    /// The callbacks of `class` see every access to the global object, which
+    /// includes the lookups of global variables.
+    pub fn with_global_class(class: &JSClass) -> Self {
+        Self::new_in_group_with_global_class(&JSContextGroup::new(), class)
+    }
 
-        Self::new_from_raw(
-            context_group,
-            false,
-            inner,
-            true
-        )
+    /// Create a new `JSContext` object in `group`.
+    pub fn new_in_group(group: &JSContextGroup) -> Self {
+        let inner = unsafe { JSGlobalContextCreateInGroup(*group.inner, std::ptr::null_mut()) };
+
+        let context = Self::new_from_raw(group.clone(), inner, true);
+        context.state.intrinsics();
+        context
     }
 
-    fn new_from_raw(context_group: JSContextGroupRef, context_group_already_retained: bool, inner: JSGlobalContextRef, inner_already_retained: bool) -> Self {
-        let context_group = RetainReleaseWrapper::<JSContextGroupRef>::new(
-            context_group,
-            context_group_already_retained,
-            |x| unsafe { JSContextGroupRetain(x); }, 
-            |x| unsafe { JSContextGroupRelease(x) }
-        );
+    /// Create a new `JSContext` object in `group`, whose global object is an
+    /// instance of `class`.
+    pub fn new_in_group_with_global_class(group: &JSContextGroup, class: &JSClass) -> Self {
+        let inner = unsafe { JSGlobalContextCreateInGroup(*group.inner, *class.inner) };
 
+        let context = Self::new_from_raw(group.clone(), inner, true);
+        context.state.intrinsics();
+        if let Some(prototype) = context.get_global_object().prototype(&context) {
+            class.install_members(&context, &prototype);
+        }
+        context
+    }
+
+    /// Create a new `JSContext` in the same group as `self`. These new independent context will be able
+    /// to share objects but they will have different global objects.
+    pub fn new_sibling(&self) -> Self {
+        Self::new_in_group(&self.context_group)
+    }
+
+    fn new_from_raw(context_group: JSContextGroup, inner: JSGlobalContextRef, inner_already_retained: bool) -> Self {
         let inner = 
             RetainReleaseWrapper::<JSGlobalContextRef>::new(
                 inner,
@@ -73,10 +105,39 @@ impl JSContext {
                 |x| unsafe { JSGlobalContextRelease(x); }
             );
 
+        let state = ContextState::for_context(*inner);
+
         Self {
             context_group,
             inner,
+            state,
+        }
+    }
+
+    /// Returns the name of this context, if it has one.
+    pub fn name(&self) -> Option<String> {
+        let name = unsafe { JSGlobalContextCopyName(*self.inner) };
+        if name.is_null() {
+            return None;
         }
+        Some(JSString::from(name).to_string())
+    }
+
+    /// Sets the name of this context.
+    pub fn set_name(&self, name: impl Into<JSString>) {
+        let name = name.into();
+        unsafe { JSGlobalContextSetName(*self.inner, name.inner) }
+    }
+
+    /// Returns the group this context belongs to.
+    pub fn group(&self) -> JSContextGroup {
+        self.context_group.clone()
+    }
+
+    /// Checks if `self` and `other` belong to the same context group, and can
+    /// therefore share values.
+    pub fn is_same_group(&self, other: &JSContext) -> bool {
+        self.context_group == other.context_group
     }
 }
 
@@ -96,8 +157,8 @@ impl JSContext {
     /// Evaluate the script.
     ///
     /// Returns the value the script evaluates to. If the script throws an
-    /// exception, this function returns `None`. You can query the thrown
-    /// exception with the `get_exception` method.
+    /// exception, this function returns it as the error. If the script is
+    /// terminated, the error's `termination` method tells why.
     pub fn evaluate_script(
         &self,
         script: &str,
@@ -106,17 +167,33 @@ impl JSContext {
         let script = JSString::from_utf8(script.to_string());
         let this_object = std::ptr::null_mut();
         let source_url = std::ptr::null_mut();
-        let mut exception: JSValueRef = std::ptr::null_mut();
-        let value = unsafe {
+        self.run_script(|exception| unsafe {
             JSEvaluateScript(
                 self.inner(),
                 script.inner,
                 this_object,
                 source_url,
                 starting_line_number,
-                &mut exception,
+                exception,
             )
-        };
+        })
+    }
+
+    /// Runs `evaluate` under the execution limits of this context.
+    pub(crate) fn run_script(
+        &self,
+        evaluate: impl FnOnce(*mut JSValueRef) -> JSValueRef,
+    ) -> Result<JSValue, JSException> {
+        let mut exception: JSValueRef = std::ptr::null_mut();
+        let heap_limit = &self.context_group.state.heap_limit;
+        heap_limit.enter(self.inner());
+        self.state.execution.enter();
+        let value = evaluate(&mut exception);
+        heap_limit.exit();
+        if let Some(reason) = self.state.execution.exit() {
+            let value = (!exception.is_null()).then(|| JSValue::from(exception));
+            return Err(JSException::terminated(self, reason, value));
+        }
         if !exception.is_null() {
             return Err(JSException::new(self, JSValue::from(exception)));
         }
@@ -124,11 +201,121 @@ impl JSContext {
     }
 }
 
+impl JSContext {
+    /// Returns a handle that other threads can use to post tasks to this
+    /// context.
+    pub fn handle(&self) -> ContextHandle {
+        ContextHandle::new(self.state.tasks.clone())
+    }
+
+    /// Runs the tasks posted through a `ContextHandle` so far and returns how
+    /// many ran. Tasks posted while running are left for the next call.
+    pub fn run_pending_tasks(&self) -> usize {
+        let tasks = self.state.tasks.take();
+        let count = tasks.len();
+        for task in tasks {
+            task(self);
+        }
+        count
+    }
+
+    /// Blocks until a task is posted through a `ContextHandle` or the timeout
+    /// expires. Returns `true` if there are tasks to run.
+    pub fn wait_for_tasks(&self, timeout: Option<Duration>) -> bool {
+        self.state.tasks.wait(timeout)
+    }
+}
+
+impl JSContext {
+    /// Asks the garbage collector to run. The collection may happen later, on
+    /// another thread, or be skipped if there is nothing worth collecting.
+    pub fn garbage_collect(&self) {
+        unsafe { JSGarbageCollect(self.inner()) }
+    }
+
+    /// Runs a full garbage collection and waits for it to finish.
+    pub fn garbage_collect_sync(&self) {
+        unsafe { JSSynchronousGarbageCollectForDebugging(self.inner()) }
+    }
+
+    /// Tells the garbage collector that the objects in this context keep
+    /// `bytes` alive outside of the JavaScript heap, such as buffers owned by
+    /// Rust, so that it collects more eagerly.
+    pub fn report_extra_memory_cost(&self, bytes: usize) {
+        unsafe { JSReportExtraMemoryCost(self.inner(), bytes as _) }
+    }
+
+    /// Returns the memory usage of the heap this context belongs to, which is
+    /// shared with the other contexts in its group.
+    pub fn heap_statistics(&self) -> Result<JSHeapStatistics, JSException> {
+        let statistics = unsafe { JSGetMemoryUsageStatistics(self.inner()) };
+        if statistics.is_null() {
+            return Err(JSException::from("Heap statistics are not available"));
+        }
+        JSHeapStatistics::from_object(self, &JSObject::from(statistics))
+    }
+}
+
+impl JSContext {
+    /// Limits how long a single call to `evaluate_script` or
+    /// `JSScript::evaluate` can run, in wall clock time. Scripts running past
+    /// the limit are terminated. `None` removes the limit.
+    pub fn set_execution_time_limit(&self, limit: Option<Duration>) {
+        self.state.execution.set_time_limit(limit);
+        if limit.is_some() {
+            install_watchdog(*self.context_group.inner);
+        }
+    }
+
+    /// Returns the execution time limit of this context.
+    pub fn execution_time_limit(&self) -> Option<Duration> {
+        self.state.execution.time_limit()
+    }
+
+    /// Returns a handle that other threads can use to terminate the script
+    /// running in this context.
+    pub fn termination_handle(&self) -> TerminationHandle {
+        install_watchdog(*self.context_group.inner);
+        self.state.execution.termination_handle()
+    }
+
+    /// Attaches `data` to the context, replacing any previous value of the
+    /// same type. Callbacks get it back with `user_data`, from the `JSContext`
+    /// they receive.
+    ///
+    /// The data belongs to the global object, without being visible to
+    /// scripts: it stays attached while the context is alive, even when no
+    /// `JSContext` wrapper for it exists, and is dropped when the global object
+    /// is garbage collected. As that can
+    /// happen during a collection, the data must not own a `JSContext`, which
+    /// would also keep the context alive forever.
+    pub fn set_user_data<T: 'static>(&self, data: T) {
+        let data: Rc<dyn Any> = Rc::new(RefCell::new(data));
+        self.user_data_map().borrow_mut().insert(TypeId::of::<T>(), data);
+    }
+
+    /// Returns the data of type `T` attached to the context with
+    /// `set_user_data`.
+    pub fn user_data<T: 'static>(&self) -> Option<Rc<RefCell<T>>> {
+        let data = self.user_data_map().borrow().get(&TypeId::of::<T>())?.clone();
+        data.downcast::<RefCell<T>>().ok()
+    }
+
+    /// Detaches the data of type `T` from the context and returns it.
+    pub fn remove_user_data<T: 'static>(&self) -> Option<Rc<RefCell<T>>> {
+        let data = self.user_data_map().borrow_mut().remove(&TypeId::of::<T>())?;
+        data.downcast::<RefCell<T>>().ok()
+    }
+
+    fn user_data_map(&self) -> Rc<UserData> {
+        context_state::user_data(*self.inner)
+    }
+}
+
 impl From<JSContextRef> for JSContext {
     fn from(inner: rusty_jsc_sys::JSContextRef) -> Self {
         Self::new_from_raw(
-            unsafe { rusty_jsc_sys::JSContextGetGroup(inner) },
-            false,
+            JSContextGroup::from_raw(unsafe { rusty_jsc_sys::JSContextGetGroup(inner) }, false),
             unsafe { rusty_jsc_sys::JSContextGetGlobalContext(inner) },
             false
         )
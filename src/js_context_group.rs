@@ -0,0 +1,86 @@
+use rusty_jsc_sys::*;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context_state::GroupState;
+use crate::helpers::RetainReleaseWrapper;
+use crate::js_context::JSContext;
+use crate::js_termination::install_watchdog;
+
+/// A group of JavaScript contexts.
+///
+/// Contexts in the same group share a heap, so values created in one of them
+/// can be used in the others. Contexts in different groups are fully isolated
+/// from each other.
+#[derive(Clone)]
+pub struct JSContextGroup {
+    pub(crate) inner: RetainReleaseWrapper<JSContextGroupRef>,
+    pub(crate) state: Rc<GroupState>,
+}
+
+impl fmt::Debug for JSContextGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JSContextGroup").field(&*self.inner).finish()
+    }
+}
+
+impl Default for JSContextGroup {
+    fn default() -> Self {
+        JSContextGroup::new()
+    }
+}
+
+impl PartialEq for JSContextGroup {
+    fn eq(&self, other: &Self) -> bool {
+        *self.inner == *other.inner
+    }
+}
+
+impl Eq for JSContextGroup {}
+
+impl JSContextGroup {
+    /// Create a new, empty, `JSContextGroup`.
+    pub fn new() -> Self {
+        Self::from_raw(unsafe { JSContextGroupCreate() }, true)
+    }
+
+    pub(crate) fn from_raw(inner: JSContextGroupRef, already_retained: bool) -> Self {
+        let inner = RetainReleaseWrapper::<JSContextGroupRef>::new(
+            inner,
+            already_retained,
+            |x| unsafe { JSContextGroupRetain(x); },
+            |x| unsafe { JSContextGroupRelease(x) }
+        );
+
+        let state = GroupState::for_group(*inner);
+
+        Self { inner, state }
+    }
+
+    /// Create a new `JSContext` in this group.
+    pub fn new_context(&self) -> JSContext {
+        JSContext::new_in_group(self)
+    }
+
+    /// Limits the memory of the heap shared by the contexts of this group,
+    /// including the memory behind array buffers and typed arrays. Scripts
+    /// that grow it past `bytes` are terminated, and `evaluate_script` fails
+    /// with `TerminationReason::HeapLimitExceeded`. `None` removes the limit.
+    ///
+    /// The limit is checked every 10 ms while a script runs. On Linux, the
+    /// size checked is the heap size when the evaluation started plus the
+    /// growth of the memory of the whole process since then, so memory
+    /// allocated by other threads during the evaluation counts as well. On
+    /// other platforms, the heap itself is measured on every check.
+    pub fn set_heap_limit(&self, bytes: Option<usize>) {
+        self.state.heap_limit.set(bytes);
+        if bytes.is_some() {
+            install_watchdog(*self.inner);
+        }
+    }
+
+    /// Returns the heap size limit of this group.
+    pub fn heap_limit(&self) -> Option<usize> {
+        self.state.heap_limit.get()
+    }
+}
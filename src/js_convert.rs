@@ -0,0 +1,206 @@
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut};
+
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// Conversion from a JavaScript value to a Rust value.
+///
+/// Primitive types follow JavaScript's own conversions, so a string argument
+/// can be read as a number and any value can be read as a `String`.
+pub trait FromJs: Sized {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException>;
+}
+
+/// Conversion from a Rust value to a JavaScript value.
+pub trait IntoJs {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException>;
+}
+
+impl FromJs for JSValue {
+    fn from_js(_context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        Ok(value.clone())
+    }
+}
+
+impl IntoJs for JSValue {
+    fn into_js(self, _context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(self)
+    }
+}
+
+impl FromJs for JSObject {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        value.to_object(context)
+    }
+}
+
+impl IntoJs for JSObject {
+    fn into_js(self, _context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(self.into())
+    }
+}
+
+impl FromJs for bool {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        Ok(value.to_bool(context))
+    }
+}
+
+impl IntoJs for bool {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(JSValue::boolean(context, self))
+    }
+}
+
+macro_rules! impl_number {
+    ($($ty:ty),*) => {
+        $(
+            impl FromJs for $ty {
+                fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+                    Ok(value.to_number(context)? as $ty)
+                }
+            }
+
+            impl IntoJs for $ty {
+                fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+                    Ok(JSValue::number(context, self as f64))
+                }
+            }
+        )*
+    };
+}
+
+impl_number!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromJs for String {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        Ok(value.to_string(context)?.to_string())
+    }
+}
+
+impl IntoJs for String {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(JSValue::string(context, self))
+    }
+}
+
+impl IntoJs for &str {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(JSValue::string(context, self))
+    }
+}
+
+impl IntoJs for () {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        Ok(JSValue::undefined(context))
+    }
+}
+
+/// `undefined` and `null` convert to `None`.
+impl<T: FromJs> FromJs for Option<T> {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        if value.is_undefined(context) || value.is_null(context) {
+            return Ok(None);
+        }
+        T::from_js(context, value).map(Some)
+    }
+}
+
+/// `None` converts to `undefined`.
+impl<T: IntoJs> IntoJs for Option<T> {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        match self {
+            Some(value) => value.into_js(context),
+            None => Ok(JSValue::undefined(context)),
+        }
+    }
+}
+
+impl<T: FromJs> FromJs for Vec<T> {
+    fn from_js(context: &JSContext, value: &JSValue) -> Result<Self, JSException> {
+        if !value.is_array(context) {
+            return Err(JSException::from("Expected an array"));
+        }
+        let array = value.to_object(context)?;
+        let length = u32::from_js(context, &array.get_property(context, "length"))?;
+        (0..length)
+            .map(|index| T::from_js(context, &array.get_property_at_index(context, index)?))
+            .collect()
+    }
+}
+
+impl<T: IntoJs> IntoJs for Vec<T> {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        let values = self
+            .into_iter()
+            .map(|value| value.into_js(context))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(JSObject::new_array(context, &values)?.into())
+    }
+}
+
+impl<T: IntoJs + Copy> IntoJs for Cell<T> {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        self.get().into_js(context)
+    }
+}
+
+impl<T: IntoJs> IntoJs for RefCell<T> {
+    fn into_js(self, context: &JSContext) -> Result<JSValue, JSException> {
+        self.into_inner().into_js(context)
+    }
+}
+
+/// The remaining arguments of a `#[callback]`, each converted with `FromJs`,
+/// like a rest parameter (`...args`) in JavaScript.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rest<T>(pub Vec<T>);
+
+impl<T> Deref for Rest<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Vec<T> {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Rest<T> {
+    fn deref_mut(&mut self) -> &mut Vec<T> {
+        &mut self.0
+    }
+}
+
+impl<T> IntoIterator for Rest<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// A field that can be assigned from JavaScript through a shared reference,
+/// as objects exposed with `#[derive(JsClass)]` are only ever borrowed.
+pub trait SetFromJs {
+    fn set_from_js(&self, context: &JSContext, value: &JSValue) -> Result<(), JSException>;
+}
+
+impl<T: FromJs> SetFromJs for Cell<T> {
+    fn set_from_js(&self, context: &JSContext, value: &JSValue) -> Result<(), JSException> {
+        self.set(T::from_js(context, value)?);
+        Ok(())
+    }
+}
+
+impl<T: FromJs> SetFromJs for RefCell<T> {
+    fn set_from_js(&self, context: &JSContext, value: &JSValue) -> Result<(), JSException> {
+        let value = T::from_js(context, value)?;
+        *self
+            .try_borrow_mut()
+            .map_err(|_| JSException::from("Can't assign a property that is being used"))? = value;
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use std::error::Error;
 use std::fmt::Display;
 
 use crate::js_context::JSContext;
+use crate::js_termination::TerminationReason;
 use crate::js_value::JSValue;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -9,6 +10,9 @@ enum JSExceptionBody {
     JSValue(JSValue),
     JSValueWithRepresentation(JSValue, String),
     String(String),
+    /// The exception JavaScriptCore raised to unwind the script, if any, which
+    /// scripts can't catch.
+    Terminated(TerminationReason, Option<JSValue>),
 }
 
 /// A JavaScript exception, formally a value.
@@ -16,6 +20,7 @@ enum JSExceptionBody {
 pub struct JSException {
     body: JSExceptionBody,
     location: String,
+    context_name: Option<String>,
 }
 
 impl JSException {
@@ -34,14 +39,64 @@ impl JSException {
             JSExceptionBody::JSValue(value)
         };
 
-        JSException { body: body.into(), location }
+        JSException { body: body.into(), location, context_name: context.name() }
     }
 
+    /// Creates an exception holding a JavaScript `TypeError` with `message`.
+    #[track_caller]
+    pub fn type_error(context: &JSContext, message: impl Into<String>) -> Self {
+        let message = message.into();
+        let error = context
+            .get_global_object()
+            .get_property(context, "TypeError")
+            .to_object(context)
+            .and_then(|constructor| {
+                constructor.construct(context, &[JSValue::string(context, message.clone())])
+            });
+        match error {
+            Ok(error) => JSException::new(context, error.into()),
+            Err(_) => JSException::from(message),
+        }
+    }
+
+    /// Creates the exception returned when a script is terminated. `value` is
+    /// the exception the script was aborted with.
+    #[track_caller]
+    pub(crate) fn terminated(context: &JSContext, reason: TerminationReason, value: Option<JSValue>) -> Self {
+        let location = std::panic::Location::caller().to_string();
+        JSException { body: JSExceptionBody::Terminated(reason, value), location, context_name: context.name() }
+    }
+
+    /// Returns why the script was terminated, if the exception comes from a
+    /// terminated script rather than from a thrown value.
+    pub fn termination(&self) -> Option<TerminationReason> {
+        match &self.body {
+            JSExceptionBody::Terminated(reason, _) => Some(*reason),
+            _ => None,
+        }
+    }
+
+    /// Checks if the exception comes from a terminated script.
+    pub fn is_terminated(&self) -> bool {
+        self.termination().is_some()
+    }
+
+    /// Returns the name of the context the exception was thrown in, if the
+    /// context has a name.
+    pub fn context_name(&self) -> Option<&str> {
+        self.context_name.as_deref()
+    }
+
+    /// Returns the thrown value. For a terminated script, this is the
+    /// exception it was aborted with: a callback rethrowing it keeps the
+    /// script from catching the termination.
     pub fn to_jsvalue(&self, context: &JSContext) -> JSValue {
         match &self.body {
             JSExceptionBody::JSValue(value) => value.clone(),
             JSExceptionBody::JSValueWithRepresentation(value, _) => value.clone(),
             JSExceptionBody::String(string) => JSValue::string(&context, string.clone()),
+            JSExceptionBody::Terminated(_, Some(value)) => value.clone(),
+            JSExceptionBody::Terminated(reason, None) => JSValue::string(context, format!("Script terminated: {}", reason)),
         }
     }
 }
@@ -54,9 +109,13 @@ impl Display for JSException {
             JSExceptionBody::JSValue(value) => format!("<error is not representable. JSValueRef={:p}>", value.inner),
             JSExceptionBody::JSValueWithRepresentation(_, msg) => msg.clone(),
             JSExceptionBody::String(msg) => msg.clone(),
+            JSExceptionBody::Terminated(reason, _) => format!("script terminated: {}", reason),
         };
 
-        write!(f, "JSException {}: {}", self.location, message)
+        match &self.context_name {
+            Some(name) => write!(f, "JSException {} in context \"{}\": {}", self.location, name, message),
+            None => write!(f, "JSException {}: {}", self.location, message),
+        }
     }
 }
 
@@ -64,7 +123,7 @@ impl From<String> for JSException {
     #[track_caller]
     fn from(string: String) -> Self {
         let location = std::panic::Location::caller().to_string();
-        JSException { body: JSExceptionBody::String(string).into(), location }
+        JSException { body: JSExceptionBody::String(string).into(), location, context_name: None }
     }
 }
 
@@ -72,6 +131,6 @@ impl From<&str> for JSException {
     #[track_caller]
     fn from(string: &str) -> Self {
         let location = std::panic::Location::caller().to_string();
-        JSException { body: JSExceptionBody::String(string.to_string()).into(), location }
+        JSException { body: JSExceptionBody::String(string.to_string()).into(), location, context_name: None }
     }
 }
\ No newline at end of file
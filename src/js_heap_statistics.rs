@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::JSException;
+
+/// A snapshot of the heap of a context group, as reported by JavaScriptCore.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JSHeapStatistics {
+    /// Bytes currently used by the heap.
+    pub heap_size: usize,
+    /// Bytes currently reserved by the heap.
+    pub heap_capacity: usize,
+    /// Bytes reported with `JSContext::report_extra_memory_cost` and held by
+    /// the engine outside of the heap.
+    pub extra_memory_size: usize,
+    /// Number of live objects.
+    pub object_count: usize,
+    /// Number of objects protected from garbage collection.
+    pub protected_object_count: usize,
+    /// Number of global objects, one per context.
+    pub global_object_count: usize,
+    /// Number of protected global objects.
+    pub protected_global_object_count: usize,
+    /// Number of live objects of each type, keyed by class name.
+    pub object_type_counts: HashMap<String, usize>,
+}
+
+impl JSHeapStatistics {
+    pub(crate) fn from_object(context: &JSContext, statistics: &JSObject) -> Result<Self, JSException> {
+        let count = |name: &str| -> Result<usize, JSException> {
+            Ok(statistics.get_property(context, name).to_number(context)? as usize)
+        };
+
+        let mut type_counts = statistics.get_property(context, "objectTypeCounts").to_object(context)?;
+        let mut object_type_counts = HashMap::new();
+        for name in type_counts.get_property_names(context) {
+            let value = type_counts.get_property(context, name.as_str()).to_number(context)?;
+            object_type_counts.insert(name, value as usize);
+        }
+
+        Ok(JSHeapStatistics {
+            heap_size: count("heapSize")?,
+            heap_capacity: count("heapCapacity")?,
+            extra_memory_size: count("extraMemorySize")?,
+            object_count: count("objectCount")?,
+            protected_object_count: count("protectedObjectCount")?,
+            global_object_count: count("globalObjectCount")?,
+            protected_global_object_count: count("protectedGlobalObjectCount")?,
+            object_type_counts,
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use rusty_jsc_sys::*;
+
+use crate::internal::JSString;
+use crate::js_class::{rust_class, JSClass};
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// A Rust value exposed to JavaScript as an object whose properties are
+/// resolved on access, like a `Proxy`.
+///
+/// Properties the host object does not handle fall back to the object's own
+/// properties and to its prototype chain. Assigning through `set` needs
+/// interior mutability, as the object is only ever borrowed.
+///
+/// ```ignore
+/// struct Env;
+///
+/// impl HostObject for Env {
+///     fn get(&self, context: &JSContext, name: &str) -> Result<Option<JSValue>, JSException> {
+///         Ok(std::env::var(name).ok().map(|value| JSValue::string(context, value)))
+///     }
+/// }
+///
+/// let env = Env.into_object(&context);
+/// ```
+pub trait HostObject: 'static {
+    /// The name of the class of host objects of this type.
+    const NAME: &'static str = "HostObject";
+
+    /// Set to `true` when overriding `has`. Otherwise the `in` operator calls
+    /// `get`, so it is only called once and its errors are thrown.
+    const IMPLEMENTS_HAS: bool = false;
+
+    /// Reads property `name`. Returning `None` lets the lookup continue on the
+    /// object's own properties and its prototype.
+    fn get(&self, context: &JSContext, name: &str) -> Result<Option<JSValue>, JSException>;
+
+    /// Writes property `name`. Returning `false` stores the value as a
+    /// regular property of the object.
+    fn set(&self, _context: &JSContext, _name: &str, _value: JSValue) -> Result<bool, JSException> {
+        Ok(false)
+    }
+
+    /// Checks if the host object has property `name`, for the `in` operator.
+    /// Only called if `IMPLEMENTS_HAS` is `true`, for host objects that can
+    /// answer without computing the value. Defaults to checking if `get`
+    /// returns a value.
+    fn has(&self, context: &JSContext, name: &str) -> bool {
+        matches!(self.get(context, name), Ok(Some(_)))
+    }
+
+    /// Deletes property `name`. Returning `false` deletes the regular
+    /// property of the object, if there is one.
+    fn delete(&self, _context: &JSContext, _name: &str) -> Result<bool, JSException> {
+        Ok(false)
+    }
+
+    /// Lists the property names of the host object, for `for...in` and
+    /// `Object.getOwnPropertyNames`.
+    fn keys(&self, _context: &JSContext) -> Vec<String> {
+        vec![]
+    }
+
+    /// Moves the value into a new JavaScript object. It is dropped when the
+    /// object is garbage collected.
+    fn into_object(self, context: &JSContext) -> JSObject
+    where
+        Self: Sized,
+    {
+        let class = rust_class::<Self>(|| {
+            let has = if Self::IMPLEMENTS_HAS {
+                Some(has_property::<Self> as _)
+            } else {
+                None
+            };
+            JSClass::builder(Self::NAME)
+                .get_property(Some(get_property::<Self>))
+                .set_property(Some(set_property::<Self>))
+                .has_property(has)
+                .delete_property(Some(delete_property::<Self>))
+                .get_property_names(Some(get_property_names::<Self>))
+                .build()
+        });
+        JSObject::new_with_data(context, &class, self)
+    }
+}
+
+unsafe fn property_name(name: JSStringRef) -> String {
+    JSString::from(JSStringRetain(name)).to_string()
+}
+
+unsafe extern "C" fn get_property<T: HostObject>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    name: JSStringRef,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let context = JSContext::from(ctx);
+    let object = JSObject::from(object);
+    let host = match object.downcast_ref::<T>(&context) {
+        Some(host) => host,
+        None => return std::ptr::null(),
+    };
+    match host.get(&context, &property_name(name)) {
+        Ok(Some(value)) => value.into(),
+        Ok(None) => std::ptr::null(),
+        Err(err) => {
+            *exception = err.to_jsvalue(&context).into();
+            std::ptr::null()
+        }
+    }
+}
+
+unsafe extern "C" fn set_property<T: HostObject>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    name: JSStringRef,
+    value: JSValueRef,
+    exception: *mut JSValueRef,
+) -> bool {
+    let context = JSContext::from(ctx);
+    let object = JSObject::from(object);
+    let host = match object.downcast_ref::<T>(&context) {
+        Some(host) => host,
+        None => return false,
+    };
+    match host.set(&context, &property_name(name), JSValue::from(value)) {
+        Ok(handled) => handled,
+        Err(err) => {
+            *exception = err.to_jsvalue(&context).into();
+            false
+        }
+    }
+}
+
+unsafe extern "C" fn has_property<T: HostObject>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    name: JSStringRef,
+) -> bool {
+    let context = JSContext::from(ctx);
+    let object = JSObject::from(object);
+    match object.downcast_ref::<T>(&context) {
+        Some(host) => host.has(&context, &property_name(name)),
+        None => false,
+    }
+}
+
+unsafe extern "C" fn delete_property<T: HostObject>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    name: JSStringRef,
+    exception: *mut JSValueRef,
+) -> bool {
+    let context = JSContext::from(ctx);
+    let object = JSObject::from(object);
+    let host = match object.downcast_ref::<T>(&context) {
+        Some(host) => host,
+        None => return false,
+    };
+    match host.delete(&context, &property_name(name)) {
+        Ok(handled) => handled,
+        Err(err) => {
+            *exception = err.to_jsvalue(&context).into();
+            false
+        }
+    }
+}
+
+unsafe extern "C" fn get_property_names<T: HostObject>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    property_names: JSPropertyNameAccumulatorRef,
+) {
+    let context = JSContext::from(ctx);
+    let object = JSObject::from(object);
+    if let Some(host) = object.downcast_ref::<T>(&context) {
+        for key in host.keys(&context) {
+            let key = JSString::from_utf8(key);
+            JSPropertyNameAccumulatorAddName(property_names, key.inner);
+        }
+    }
+}
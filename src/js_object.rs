@@ -2,21 +2,46 @@ use crate::internal::JSString;
 use rusty_jsc_sys::JSObjectCallAsFunctionCallback;
 use rusty_jsc_sys::*;
 use bytes::Bytes;
-use std::{ptr, os::raw::c_void};
+use std::{any::Any, ptr, os::raw::c_void, rc::Rc};
 
+use crate::closure_function::{define_accessor, getter_closure, make_function, setter_closure};
+use crate::js_class::{constructor_class, constructor_data, downcast_data, root_class, ConstructorData, JSClass};
 use crate::js_context::JSContext;
 use crate::js_value::JSValue;
 use crate::JSException;
 
+/// Wraps a native constructor in a function that gives the objects it creates
+/// the prototype of the class being constructed, which differs when a script
+/// subclasses it. The built-in functions it uses are passed in, as scripts can
+/// replace the global ones.
+const SUBCLASSABLE_CONSTRUCTOR: &str = r#"
+    'use strict';
+    const constructor = function (...args) {
+        if (new.target === undefined) {
+            throw new TypeError(`Class constructor ${name} cannot be invoked without 'new'`);
+        }
+        const object = construct(native, args);
+        if (new.target !== constructor) {
+            setPrototypeOf(object, new.target.prototype);
+        }
+        return object;
+    };
+    defineProperty(constructor, 'name', { value: name });
+    constructor.prototype = native.prototype;
+    defineProperty(native.prototype, 'constructor', {
+        value: constructor,
+        writable: true,
+        configurable: true,
+    });
+    return constructor;
+"#;
+
 /// A JavaScript object.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JSObject {
     inner: JSObjectRef,
 }
 
-unsafe impl Send for JSObject {}
-unsafe impl Sync for JSObject {}
-
 impl Drop for JSObject {
     fn drop(&mut self) {
         // TODO
@@ -35,6 +60,133 @@ impl JSObject {
         Self::from(o_ref)
     }
 
+    /// Creates an object of `class` carrying `data`. The data is dropped when
+    /// the object is garbage collected.
+    pub fn new_with_data<T: 'static>(context: &JSContext, class: &JSClass, data: T) -> Self {
+        let data: Box<Box<dyn Any>> = Box::new(Box::new(data));
+        let o_ref = unsafe { JSObjectMake(context.inner(), *class.inner, Box::into_raw(data) as _) };
+        let object = Self::from(o_ref);
+        if let Some(prototype) = object.prototype(context) {
+            class.install_members(context, &prototype);
+        }
+        object
+    }
+
+    /// Creates a constructor for the objects of `class`, to be used with `new`
+    /// from JavaScript. Its `prototype` is the prototype shared by all the
+    /// objects of `class`.
+    ///
+    /// Scripts can extend the constructor with `class Foo extends Base` and
+    /// call it with `super()`: the objects it creates for a subclass get the
+    /// subclass prototype.
+    ///
+    /// `callback` is usually generated with the `#[constructor]` macro.
+    pub fn new_constructor(
+        context: &JSContext,
+        class: &JSClass,
+        callback: JSObjectCallAsConstructorCallback,
+    ) -> Self {
+        // The class goes in the private data of the constructor, where
+        // `new_instance` finds it and scripts can't replace it.
+        let data = Box::new(ConstructorData { class: class.clone(), callback });
+        let constructor =
+            Self::from(unsafe { JSObjectMake(context.inner(), constructor_class(), Box::into_raw(data) as _) });
+        // The prototype JavaScriptCore gives the constructors of `class`.
+        let prototype = Self::from(unsafe { JSObjectMakeConstructor(context.inner(), *class.inner, None) })
+            .get_property(context, "prototype");
+        let name = JSString::from_utf8("prototype".to_string());
+        unsafe {
+            JSObjectSetProperty(
+                context.inner(),
+                constructor.inner,
+                name.inner,
+                prototype.inner,
+                kJSPropertyAttributeReadOnly | kJSPropertyAttributeDontEnum | kJSPropertyAttributeDontDelete,
+                ptr::null_mut(),
+            )
+        };
+        if let Ok(prototype) = prototype.to_object(context) {
+            class.install_members(context, &prototype);
+            let name = JSString::from_utf8("constructor".to_string());
+            unsafe {
+                JSObjectSetProperty(
+                    context.inner(),
+                    prototype.inner,
+                    name.inner,
+                    constructor.inner,
+                    kJSPropertyAttributeDontEnum,
+                    ptr::null_mut(),
+                )
+            };
+        }
+        // JavaScriptCore does not pass `new.target` to constructor callbacks, so
+        // a JavaScript function forwards it. The native constructor still works
+        // on its own if the wrapper can't be created.
+        Self::subclassable_constructor(context, &constructor, class.name()).unwrap_or(constructor)
+    }
+
+    fn subclassable_constructor(
+        context: &JSContext,
+        native: &JSObject,
+        name: &str,
+    ) -> Result<Self, JSException> {
+        let factory = Self::new_function_from_source(
+            context,
+            JSString::from_utf8("subclassableConstructor".to_string()),
+            &["native", "name", "construct", "setPrototypeOf", "defineProperty"],
+            SUBCLASSABLE_CONSTRUCTOR,
+            None,
+            1,
+        )?;
+        let intrinsics = context.state.intrinsics();
+        let arguments = [
+            native.to_jsvalue(),
+            JSValue::string(context, name),
+            intrinsics.construct()?,
+            intrinsics.set_prototype_of()?,
+            intrinsics.define_property()?,
+        ];
+        factory.call(context, None, &arguments)?.to_object(context)
+    }
+
+    /// Creates an object carrying `data` for `constructor`, which must have been
+    /// created with `new_constructor`. This is what `#[constructor]` callbacks
+    /// use to turn their result into an object.
+    ///
+    /// Returns an error for any other constructor.
+    pub fn new_instance<T: 'static>(
+        context: &JSContext,
+        constructor: &JSObject,
+        data: T,
+    ) -> Result<Self, JSException> {
+        let class = &constructor_data(context, constructor)
+            .ok_or_else(|| JSException::from("Can't create an instance: not a Rust constructor"))?
+            .class;
+        let data: Box<Box<dyn Any>> = Box::new(Box::new(data));
+        let o_ref = unsafe { JSObjectMake(context.inner(), *class.inner, Box::into_raw(data) as _) };
+        Ok(Self::from(o_ref))
+    }
+
+    /// Borrows the Rust data of the object if it holds a `T`, as created by
+    /// `new_with_data`, `new_instance`, `JsClass::into_object` or
+    /// `HostObject::into_object`.
+    ///
+    /// Objects of a `JsClass` extending the class of `T` also give access to
+    /// the `T` they hold. Returns `None` for any other object, so objects
+    /// coming from scripts can be checked safely.
+    pub fn downcast_ref<T: 'static>(&self, context: &JSContext) -> Option<&T> {
+        // Only objects of a `JSClass` carry a `Box<dyn Any>`; the private data of
+        // other objects, like the prototypes of constructors, is something else.
+        if !unsafe { JSValueIsObjectOfClass(context.inner(), self.inner, root_class()) } {
+            return None;
+        }
+        let data = unsafe { JSObjectGetPrivate(self.inner) } as *const Box<dyn Any>;
+        if data.is_null() {
+            return None;
+        }
+        downcast_data::<T>(unsafe { &**data })
+    }
+
     /// Create a new Array Object with the given arguments
     pub fn new_array(context: &JSContext, args: &[JSValue]) -> Result<Self, JSException> {
         let args_refs = args.iter().map(|arg| arg.inner).collect::<Vec<_>>();
@@ -64,23 +216,73 @@ impl JSObject {
         Self::from(o_ref)
     }
 
-    /// Calls the object constructor
-    pub fn construct(&self, context: &JSContext, args: &[JSValue]) -> Result<Self, JSException> {
-        let args_refs = args.iter().map(|arg| arg.inner).collect::<Vec<_>>();
+    /// Compiles `body` into a function called `name` taking `parameter_names`.
+    ///
+    /// `source_url` and `starting_line_number` are used to report the location
+    /// of errors, including the syntax errors this fails with.
+    pub fn new_function_from_source(
+        context: &JSContext,
+        name: impl Into<JSString>,
+        parameter_names: &[&str],
+        body: &str,
+        source_url: Option<&str>,
+        starting_line_number: i32,
+    ) -> Result<Self, JSException> {
+        let name = name.into();
+        let parameter_names = parameter_names
+            .iter()
+            .map(|name| JSString::from_utf8(name.to_string()))
+            .collect::<Vec<_>>();
+        let parameter_refs = parameter_names.iter().map(|name| name.inner).collect::<Vec<_>>();
+        let body = JSString::from_utf8(body.to_string());
+        let source_url = source_url.map(|url| JSString::from_utf8(url.to_string()));
         let mut exception: JSValueRef = std::ptr::null_mut();
         let result = unsafe {
-            JSObjectCallAsConstructor(
+            JSObjectMakeFunction(
                 context.inner(),
-                self.inner,
-                args.len() as _,
-                args_refs.as_slice().as_ptr(),
+                name.inner,
+                parameter_refs.len() as _,
+                parameter_refs.as_ptr(),
+                body.inner,
+                source_url.as_ref().map_or(std::ptr::null_mut(), |url| url.inner),
+                starting_line_number,
                 &mut exception,
             )
         };
         if !exception.is_null() {
-            return Err(JSException::new(&context, JSValue::from(exception)));
+            return Err(JSException::new(context, JSValue::from(exception)));
         }
         if result.is_null() {
+            return Err(JSException::from("Can't create a function from the provided source"));
+        }
+        Ok(Self::from(result))
+    }
+
+    /// Creates a function that calls `closure` with the context, `this` and
+    /// the arguments. The closure is dropped when the function is garbage
+    /// collected.
+    pub fn new_function_with_closure<F>(context: &JSContext, name: &str, closure: F) -> Self
+    where
+        F: Fn(&JSContext, &JSObject, &[JSValue]) -> Result<JSValue, JSException> + 'static,
+    {
+        make_function(context, name, 0, Rc::new(closure))
+    }
+
+    /// Calls the object constructor
+    ///
+    /// Runs under the execution limits of the context, like `evaluate_script`.
+    pub fn construct(&self, context: &JSContext, args: &[JSValue]) -> Result<Self, JSException> {
+        let args_refs = args.iter().map(|arg| arg.inner).collect::<Vec<_>>();
+        let result = context.run_script(|exception| unsafe {
+            JSObjectCallAsConstructor(
+                context.inner(),
+                self.inner,
+                args.len() as _,
+                args_refs.as_slice().as_ptr(),
+                exception,
+            ) as JSValueRef
+        })?;
+        if result.inner.is_null() {
             return Err(JSException::from(
                 format!(
                     "Can't call constructor for {:?}: not a valid constructor",
@@ -88,10 +290,12 @@ impl JSObject {
                 )
             ));
         }
-        Ok(Self::from(result))
+        Ok(Self::from(result.inner as JSObjectRef))
     }
 
     /// Call the object as if it a function
+    ///
+    /// Runs under the execution limits of the context, like `evaluate_script`.
     pub fn call(
         &self,
         context: &JSContext,
@@ -99,8 +303,7 @@ impl JSObject {
         args: &[JSValue],
     ) -> Result<JSValue, JSException> {
         let args_refs = args.iter().map(|arg| arg.inner).collect::<Vec<_>>();
-        let mut exception: JSValueRef = std::ptr::null_mut();
-        let result = unsafe {
+        let result = context.run_script(|exception| unsafe {
             JSObjectCallAsFunction(
                 context.inner(),
                 self.inner,
@@ -108,13 +311,10 @@ impl JSObject {
                     .unwrap_or_else(|| std::ptr::null_mut()),
                 args.len() as _,
                 args_refs.as_slice().as_ptr(),
-                &mut exception,
+                exception,
             )
-        };
-        if !exception.is_null() {
-            return Err(JSException::new(&context, JSValue::from(exception)));
-        }
-        if result.is_null() {
+        })?;
+        if result.inner.is_null() {
             return Err(JSException::from(
                 format!(
                     "Can't call the object {:?}: not a valid function",
@@ -122,7 +322,7 @@ impl JSObject {
                 ),
             ));
         }
-        Ok(JSValue::from(result))
+        Ok(result)
     }
 
     /// Calls the object constructor
@@ -130,6 +330,17 @@ impl JSObject {
         JSValue::from(self.inner)
     }
 
+    /// Returns this object, created in `from`, for use in `to`.
+    ///
+    /// Objects can only be shared between contexts of the same group, so this
+    /// fails if `from` and `to` belong to different groups.
+    pub fn transfer(&self, from: &JSContext, to: &JSContext) -> Result<JSObject, JSException> {
+        if !from.is_same_group(to) {
+            return Err(JSException::from("Can't transfer an object between contexts of different groups"));
+        }
+        Ok(self.clone())
+    }
+
     pub fn create_typed_array_with_bytes(
         context: &JSContext,
         bytes: Bytes,
@@ -310,6 +521,81 @@ impl JSObject {
         Ok(())
     }
 
+    /// Defines an accessor property called `name`, read with `getter` and
+    /// written with `setter`. Both receive the context and `this`, and the
+    /// setter the assigned value.
+    ///
+    /// The closures are dropped once the object, and any copy of the accessor
+    /// functions a script may hold, is garbage collected. Scripts replacing
+    /// `Object.defineProperty` don't affect how the accessor is defined.
+    pub fn define_accessor<G, S>(
+        &self,
+        context: &JSContext,
+        name: &str,
+        getter: G,
+        setter: S,
+        attributes: JSPropertyAttributes,
+    ) -> Result<(), JSException>
+    where
+        G: Fn(&JSContext, &JSObject) -> Result<JSValue, JSException> + 'static,
+        S: Fn(&JSContext, &JSObject, JSValue) -> Result<(), JSException> + 'static,
+    {
+        let getter = make_function(context, &format!("get {}", name), 0, getter_closure(getter));
+        let setter = make_function(context, &format!("set {}", name), 1, setter_closure(setter));
+        define_accessor(context, self, name, Some(getter), Some(setter), attributes)
+    }
+
+    /// Defines a read-only accessor property called `name`, computed by
+    /// `getter` on every read. See `define_accessor`.
+    pub fn define_getter<G>(
+        &self,
+        context: &JSContext,
+        name: &str,
+        getter: G,
+        attributes: JSPropertyAttributes,
+    ) -> Result<(), JSException>
+    where
+        G: Fn(&JSContext, &JSObject) -> Result<JSValue, JSException> + 'static,
+    {
+        let getter = make_function(context, &format!("get {}", name), 0, getter_closure(getter));
+        define_accessor(context, self, name, Some(getter), None, attributes)
+    }
+
+    /// Sets the property of an object with `attributes`.
+    pub(crate) fn set_property_with_attributes(
+        &self,
+        context: &JSContext,
+        property_name: impl Into<JSString>,
+        value: JSValue,
+        attributes: JSPropertyAttributes,
+    ) -> Result<(), JSException> {
+        let property_name = property_name.into();
+        let mut exception: JSValueRef = std::ptr::null_mut();
+        unsafe {
+            JSObjectSetProperty(
+                context.inner(),
+                self.inner,
+                property_name.inner,
+                value.inner,
+                attributes,
+                &mut exception,
+            )
+        }
+        if !exception.is_null() {
+            return Err(JSException::new(context, JSValue::from(exception)));
+        }
+        Ok(())
+    }
+
+    /// Returns the prototype of the object, if it is an object.
+    pub(crate) fn prototype(&self, context: &JSContext) -> Option<JSObject> {
+        let prototype = JSValue::from(unsafe { JSObjectGetPrototype(context.inner(), self.inner) });
+        if !prototype.is_object(context) {
+            return None;
+        }
+        prototype.to_object(context).ok()
+    }
+
     /// Sets the property of an object at a given index
     pub fn set_property_at_index(
         &self,
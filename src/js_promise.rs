@@ -1,5 +1,5 @@
 use crate::{JSContext, JSValue, JSObject, JSException};
-use crate::js_protected_value::JSProtectedValue;
+use crate::js_protected_value::{JSProtectedValue, Persistent};
 use rusty_jsc_sys::*;
 
 pub struct JSPromise<T> {
@@ -25,11 +25,8 @@ impl<T> JSPromise<T> where T: Into<JSValue> {
         } else if promise.is_null(&context) {
             Err(JSException::from("Promise could not be created, no explicit error was reported."))
         } else {
-            let resolve = JSObject::from(resolve);
-            let reject = JSObject::from(reject);
-
-            unsafe { JSValueProtect(context.inner(), resolve.to_jsvalue().inner) };
-            unsafe { JSValueProtect(context.inner(), reject.to_jsvalue().inner) };
+            let resolve = Persistent::new(&context, JSObject::from(resolve));
+            let reject = Persistent::new(&context, JSObject::from(reject));
 
             Ok(JSPromise {
                 value: JSProtectedValue::new(&context, promise),
@@ -38,9 +35,6 @@ impl<T> JSPromise<T> where T: Into<JSValue> {
                         Ok(value) => resolve.call(&context, None, &[value.into()]).unwrap(),
                         Err(error) => reject.call(&context, None, &[error.to_jsvalue(&context)]).unwrap()
                     };
-
-                    unsafe { JSValueUnprotect(context.inner(), resolve.to_jsvalue().inner) };
-                    unsafe { JSValueUnprotect(context.inner(), reject.to_jsvalue().inner) };        
                 }),
             })
         }
@@ -59,15 +53,8 @@ impl<T> JSPromise<T> where T: Into<JSValue> {
     }
 }
 
-// TODO: This is wrong becuase of the use of the cloned context internally.
-// While JSContext can be cloned, it's not Sync yet and therefore should not
-// be called in other threads. By making this Send, if the promise is resolved
-// elsewhere then there might be a misuse of JSContext in mulitple threads at
-// the same time.
-unsafe impl<T> Send for JSPromise<T> {}
-
 impl<T> From<&JSPromise<T>> for JSValue {
     fn from(promise: &JSPromise<T>) -> Self {
-        promise.value.clone()
+        promise.value.get().clone()
     }
 }
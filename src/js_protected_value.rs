@@ -1,30 +1,169 @@
+use std::mem::ManuallyDrop;
 use std::ops::Deref;
+use std::thread::{self, ThreadId};
 
-use rusty_jsc_sys::{JSValueProtect, JSValueUnprotect};
-use crate::{JSContext, JSValue};
+use rusty_jsc_sys::*;
 
-pub struct JSProtectedValue(JSValue, JSContext);
+use crate::helpers::RetainReleaseWrapper;
+use crate::{ContextHandle, JSContext, JSObject, JSValue};
 
-impl JSProtectedValue {
-    // TODO: Not making this public as this requires JSContext to be sync.
-    // There's no good way yet to encode that guarantee so keeping it as
-    // a convenience pattern for JSContext.
-    pub(crate) fn new(context: &JSContext, value: JSValue) -> Self {
-        unsafe { JSValueProtect(context.inner(), value.inner) };
-        JSProtectedValue(value, context.clone())
+/// A value that can be rooted by a `Persistent` handle.
+pub trait Protectable: Clone {
+    #[doc(hidden)]
+    fn value_ref(&self) -> JSValueRef;
+}
+
+impl Protectable for JSValue {
+    fn value_ref(&self) -> JSValueRef {
+        self.inner
+    }
+}
+
+impl Protectable for JSObject {
+    fn value_ref(&self) -> JSValueRef {
+        self.to_jsvalue().inner
     }
 }
 
-impl Drop for JSProtectedValue {
+/// A handle that keeps a JavaScript value alive for as long as Rust holds it.
+///
+/// Creating the handle protects the value from garbage collection, every clone
+/// protects it once more and dropping a handle unprotects it. The handle can be
+/// stored in structs that move between threads, but the value itself can only
+/// be accessed from the thread that created the handle, which is the thread
+/// that owns the context.
+///
+/// A handle dropped on another thread is released on the owning thread, the
+/// next time it runs the tasks of the context. If the context no longer runs
+/// them, the value stays protected and the context is never released.
+pub struct Persistent<T: Protectable> {
+    value: T,
+    root: Option<Root>,
+    handle: ContextHandle,
+    owner: ThreadId,
+}
+
+/// The protection of a value, along with the global context it keeps alive.
+/// Releasing the context can destroy the heap and run the finalizers of its
+/// objects, so it is only dropped on the thread owning the context.
+struct Root {
+    value: JSValueRef,
+    context: RetainReleaseWrapper<JSGlobalContextRef>,
+}
+
+impl Drop for Root {
     fn drop(&mut self) {
-        unsafe { JSValueUnprotect(self.1.inner(), self.0.inner) }
+        unsafe { JSValueUnprotect(*self.context, self.value) }
     }
 }
 
-impl Deref for JSProtectedValue {
-    type Target = JSValue;
+// Only dropped on the thread owning the context, see `Persistent::drop`.
+unsafe impl Send for Root {}
+
+/// A rooted `JSValue`.
+pub type JSProtectedValue = Persistent<JSValue>;
+
+impl<T: Protectable> Persistent<T> {
+    /// Roots `value` in `context` until the returned handle and all its clones
+    /// are dropped.
+    pub fn new(context: &JSContext, value: T) -> Self {
+        unsafe { JSValueProtect(context.inner(), value.value_ref()) };
+        let root = Root {
+            value: value.value_ref(),
+            context: context.inner.clone(),
+        };
+        Persistent {
+            value,
+            root: Some(root),
+            handle: context.handle(),
+            owner: thread::current().id(),
+        }
+    }
+
+    /// Returns `true` if the value can be accessed from the current thread.
+    pub fn is_accessible(&self) -> bool {
+        thread::current().id() == self.owner
+    }
+
+    /// Returns the rooted value, or `None` when called from a thread other than
+    /// the one owning the context.
+    pub fn try_get(&self) -> Option<&T> {
+        if self.is_accessible() {
+            Some(&self.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rooted value.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called from a thread other than the one owning the context.
+    pub fn get(&self) -> &T {
+        match self.try_get() {
+            Some(value) => value,
+            None => panic!(
+                "Persistent value accessed from {:?} but it is owned by {:?}",
+                thread::current().id(),
+                self.owner
+            ),
+        }
+    }
+}
+
+impl<T: Protectable> Clone for Persistent<T> {
+    fn clone(&self) -> Self {
+        let context = self.root.as_ref().unwrap().context.clone();
+        unsafe { JSValueProtect(*context, self.value.value_ref()) };
+        Persistent {
+            value: self.value.clone(),
+            root: Some(Root {
+                value: self.value.value_ref(),
+                context,
+            }),
+            handle: self.handle.clone(),
+            owner: self.owner,
+        }
+    }
+}
+
+impl<T: Protectable> Drop for Persistent<T> {
+    fn drop(&mut self) {
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => return,
+        };
+        if self.is_accessible() {
+            return drop(root);
+        }
+        // A task that never runs is dropped wherever the queue is, so the root
+        // is leaked unless the owning thread runs it.
+        let root = ManuallyDrop::new(root);
+        let _ = self.handle.post(move |_| drop(ManuallyDrop::into_inner(root)));
+    }
+}
+
+impl<T: Protectable> Deref for Persistent<T> {
+    type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        self.get()
     }
 }
+
+impl<T: Protectable> std::fmt::Debug for Persistent<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Persistent")
+            .field("value", &self.value.value_ref())
+            .field("owner", &self.owner)
+            .finish()
+    }
+}
+
+// The value is only handed out on the owning thread (see `try_get`), and the
+// context is only released there (see `Drop`). Protecting the value from
+// another thread, when cloning the handle, goes through JavaScriptCore's API
+// lock.
+unsafe impl<T: Protectable> Send for Persistent<T> {}
+unsafe impl<T: Protectable> Sync for Persistent<T> {}
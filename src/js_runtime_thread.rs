@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+use crate::{ContextHandle, JSContext};
+
+/// Default number of jobs a `JSRuntimeThread` queues before applying
+/// backpressure.
+pub const DEFAULT_RUNTIME_QUEUE_CAPACITY: usize = 1024;
+
+/// Error returned by the jobs submitted to a `JSRuntimeThread`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JSRuntimeError {
+    /// The queue was full and the job was not accepted.
+    QueueFull,
+    /// The runtime was shut down before the job could run.
+    ShutDown,
+    /// The job panicked; contains the panic message.
+    Panicked(String),
+}
+
+impl Error for JSRuntimeError {}
+
+impl Display for JSRuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JSRuntimeError::QueueFull => write!(f, "the runtime queue is full"),
+            JSRuntimeError::ShutDown => write!(f, "the runtime has been shut down"),
+            JSRuntimeError::Panicked(msg) => write!(f, "the job panicked: {}", msg),
+        }
+    }
+}
+
+/// Counts the jobs that were accepted but have not finished yet.
+struct Slots {
+    used: Mutex<usize>,
+    freed: Condvar,
+    capacity: usize,
+}
+
+impl Slots {
+    fn try_acquire(self: &Arc<Self>) -> Option<SlotGuard> {
+        let mut used = self.used.lock().unwrap();
+        if *used >= self.capacity {
+            return None;
+        }
+        *used += 1;
+        Some(SlotGuard(self.clone()))
+    }
+
+    fn acquire(self: &Arc<Self>) -> SlotGuard {
+        let used = self.used.lock().unwrap();
+        let mut used = self.freed.wait_while(used, |used| *used >= self.capacity).unwrap();
+        *used += 1;
+        SlotGuard(self.clone())
+    }
+}
+
+/// Frees its slot when the job finishes or is dropped without running.
+struct SlotGuard(Arc<Slots>);
+
+impl Drop for SlotGuard {
+    fn drop(&mut self) {
+        *self.0.used.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+struct Shared<R> {
+    result: Option<Result<R, JSRuntimeError>>,
+    waker: Option<Waker>,
+}
+
+/// Completes a `JSRuntimeFuture`. Dropping it without a result, because the
+/// runtime went away before running the job, completes the future with
+/// `JSRuntimeError::ShutDown`.
+struct Completion<R>(Option<Arc<Mutex<Shared<R>>>>);
+
+impl<R> Completion<R> {
+    fn complete(mut self, result: Result<R, JSRuntimeError>) {
+        if let Some(shared) = self.0.take() {
+            Self::resolve(&shared, result);
+        }
+    }
+
+    fn resolve(shared: &Mutex<Shared<R>>, result: Result<R, JSRuntimeError>) {
+        let mut shared = shared.lock().unwrap();
+        shared.result = Some(result);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<R> Drop for Completion<R> {
+    fn drop(&mut self) {
+        if let Some(shared) = self.0.take() {
+            Self::resolve(&shared, Err(JSRuntimeError::ShutDown));
+        }
+    }
+}
+
+/// The result of `JSRuntimeThread::run`, resolved once the job has run on the
+/// runtime thread.
+pub struct JSRuntimeFuture<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+}
+
+impl<R> JSRuntimeFuture<R> {
+    fn ready(result: Result<R, JSRuntimeError>) -> Self {
+        JSRuntimeFuture {
+            shared: Arc::new(Mutex::new(Shared {
+                result: Some(result),
+                waker: None,
+            })),
+        }
+    }
+}
+
+impl<R> Future for JSRuntimeFuture<R> {
+    type Output = Result<R, JSRuntimeError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+fn run_job<F, R>(context: &JSContext, job: F) -> Result<R, JSRuntimeError>
+where
+    F: FnOnce(&JSContext) -> R,
+{
+    catch_unwind(AssertUnwindSafe(|| job(context))).map_err(|panic| {
+        let msg = if let Some(msg) = panic.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = panic.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        JSRuntimeError::Panicked(msg)
+    })
+}
+
+/// A `JSContext` living on its own OS thread.
+///
+/// Jobs can be submitted from any thread, either awaiting the result with
+/// `run` or blocking on it with `run_blocking`. Jobs run one at a time in
+/// submission order. At most `capacity` jobs can be queued or running at the
+/// same time: `run` fails with `JSRuntimeError::QueueFull` past that point
+/// while `run_blocking` waits for a free slot.
+///
+/// Dropping the runtime, or calling `shutdown`, stops accepting jobs, runs the
+/// ones already queued and joins the thread.
+pub struct JSRuntimeThread {
+    handle: ContextHandle,
+    slots: Arc<Slots>,
+    shutting_down: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Default for JSRuntimeThread {
+    fn default() -> Self {
+        JSRuntimeThread::new()
+    }
+}
+
+impl JSRuntimeThread {
+    /// Starts a runtime thread with `DEFAULT_RUNTIME_QUEUE_CAPACITY`.
+    pub fn new() -> Self {
+        JSRuntimeThread::with_capacity(DEFAULT_RUNTIME_QUEUE_CAPACITY)
+    }
+
+    /// Starts a runtime thread that accepts at most `capacity` pending jobs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "the runtime queue capacity must be positive");
+
+        let shutting_down = Arc::new(AtomicBool::new(false));
+        let (handle_sender, handle_receiver) = mpsc::channel();
+        let thread = {
+            let shutting_down = shutting_down.clone();
+            thread::Builder::new()
+                .name("rusty_jsc-runtime".to_string())
+                .spawn(move || {
+                    let context = JSContext::new();
+                    handle_sender.send(context.handle()).unwrap();
+                    loop {
+                        context.wait_for_tasks(None);
+                        context.run_pending_tasks();
+                        if shutting_down.load(Ordering::SeqCst) {
+                            while context.run_pending_tasks() > 0 {}
+                            break;
+                        }
+                    }
+                })
+                .expect("failed to spawn the runtime thread")
+        };
+        let handle = handle_receiver.recv().expect("the runtime thread failed to start");
+
+        JSRuntimeThread {
+            handle,
+            slots: Arc::new(Slots {
+                used: Mutex::new(0),
+                freed: Condvar::new(),
+                capacity,
+            }),
+            shutting_down,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns a handle to the runtime's context. Tasks posted through it
+    /// bypass the queue capacity.
+    pub fn handle(&self) -> ContextHandle {
+        self.handle.clone()
+    }
+
+    /// Runs `job` on the runtime thread and returns a future resolving to its
+    /// result. Fails right away with `JSRuntimeError::QueueFull` if the queue
+    /// is full.
+    pub fn run<F, R>(&self, job: F) -> JSRuntimeFuture<R>
+    where
+        F: FnOnce(&JSContext) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return JSRuntimeFuture::ready(Err(JSRuntimeError::ShutDown));
+        }
+        let slot = match self.slots.try_acquire() {
+            Some(slot) => slot,
+            None => return JSRuntimeFuture::ready(Err(JSRuntimeError::QueueFull)),
+        };
+
+        let shared = Arc::new(Mutex::new(Shared {
+            result: None,
+            waker: None,
+        }));
+        let completion = Completion(Some(shared.clone()));
+        // If the post fails the completion is dropped, resolving the future.
+        let _ = self.handle.post(move |context| {
+            let _slot = slot;
+            completion.complete(run_job(context, job));
+        });
+        JSRuntimeFuture { shared }
+    }
+
+    /// Runs `job` on the runtime thread and blocks until it returns, waiting
+    /// for a free slot if the queue is full.
+    pub fn run_blocking<F, R>(&self, job: F) -> Result<R, JSRuntimeError>
+    where
+        F: FnOnce(&JSContext) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(JSRuntimeError::ShutDown);
+        }
+        let slot = self.slots.acquire();
+
+        let (sender, receiver) = mpsc::channel();
+        self.handle
+            .post(move |context| {
+                let _slot = slot;
+                let _ = sender.send(run_job(context, job));
+            })
+            .map_err(|_| JSRuntimeError::ShutDown)?;
+        receiver.recv().unwrap_or(Err(JSRuntimeError::ShutDown))
+    }
+
+    /// Stops accepting jobs, runs the ones already queued and waits for the
+    /// runtime thread to exit.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.shutting_down.store(true, Ordering::SeqCst);
+            // Wakes the runtime thread up in case it is waiting for tasks.
+            let _ = self.handle.post(|_| {});
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for JSRuntimeThread {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl std::fmt::Debug for JSRuntimeThread {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JSRuntimeThread")
+            .field("capacity", &self.slots.capacity)
+            .field("shutting_down", &self.shutting_down.load(Ordering::SeqCst))
+            .finish()
+    }
+}
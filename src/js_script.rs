@@ -0,0 +1,92 @@
+use rusty_jsc_sys::*;
+use std::fmt;
+
+use crate::helpers::RetainReleaseWrapper;
+use crate::internal::JSString;
+use crate::js_context::JSContext;
+use crate::js_context_group::JSContextGroup;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// A script parsed once and evaluated any number of times, in any context of
+/// the group it was created for.
+#[derive(Clone)]
+pub struct JSScript {
+    inner: RetainReleaseWrapper<JSScriptRef>,
+    group: JSContextGroup,
+}
+
+impl fmt::Debug for JSScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JSScript")
+            .field("inner", &*self.inner)
+            .field("group", &self.group)
+            .finish()
+    }
+}
+
+impl JSScript {
+    /// Parses `source` for the contexts of `group`.
+    ///
+    /// `source_url` and `starting_line_number` are used to report the location
+    /// of errors. Fails with the syntax error if `source` can't be parsed.
+    pub fn new(
+        group: &JSContextGroup,
+        source: &str,
+        source_url: Option<&str>,
+        starting_line_number: i32,
+    ) -> Result<Self, JSException> {
+        let source = JSString::from_utf8(source.to_string());
+        let source_url = source_url.map(|url| JSString::from_utf8(url.to_string()));
+        let mut error_message: JSStringRef = std::ptr::null_mut();
+        let mut error_line: i32 = 0;
+        let script = unsafe {
+            JSScriptCreateFromString(
+                *group.inner,
+                source_url.as_ref().map_or(std::ptr::null_mut(), |url| url.inner),
+                starting_line_number,
+                source.inner,
+                &mut error_message,
+                &mut error_line,
+            )
+        };
+        if script.is_null() {
+            let message = if error_message.is_null() {
+                "Can't parse the script".to_string()
+            } else {
+                JSString::from(error_message).to_string()
+            };
+            return Err(JSException::from(format!("{} (line {})", message, error_line)));
+        }
+
+        let inner = RetainReleaseWrapper::<JSScriptRef>::new(
+            script,
+            true,
+            |x| unsafe { JSScriptRetain(x) },
+            |x| unsafe { JSScriptRelease(x) }
+        );
+
+        Ok(JSScript {
+            inner,
+            group: group.clone(),
+        })
+    }
+
+    /// Returns the group the script can be evaluated in.
+    pub fn group(&self) -> JSContextGroup {
+        self.group.clone()
+    }
+
+    /// Evaluates the script in `context`, which must belong to the group the
+    /// script was created for.
+    ///
+    /// Returns the value the script evaluates to, or the exception it throws.
+    pub fn evaluate(&self, context: &JSContext) -> Result<JSValue, JSException> {
+        if context.context_group != self.group {
+            return Err(JSException::from("Can't evaluate a script in a context of another group"));
+        }
+        context.run_script(|exception| unsafe {
+            JSScriptEvaluate(context.inner(), *self.inner, std::ptr::null(), exception)
+        })
+    }
+}
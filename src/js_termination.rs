@@ -0,0 +1,253 @@
+use rusty_jsc_sys::*;
+use std::cell::Cell;
+use std::fmt::Display;
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::context_state::{ContextState, GroupState};
+use crate::js_context::JSContext;
+use crate::js_object::JSObject;
+
+/// How often a running script checks whether it should be terminated.
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Why a script was terminated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// `TerminationHandle::terminate` was called.
+    Requested,
+    /// The script ran past the context's execution time limit.
+    TimedOut,
+    /// The heap of the context group grew past its limit.
+    HeapLimitExceeded,
+}
+
+impl Display for TerminationReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerminationReason::Requested => write!(f, "termination was requested"),
+            TerminationReason::TimedOut => write!(f, "execution time limit exceeded"),
+            TerminationReason::HeapLimitExceeded => write!(f, "heap size limit exceeded"),
+        }
+    }
+}
+
+/// A thread-safe handle that aborts the script running in a context.
+///
+/// Calling `terminate` while no script is running has no effect.
+#[derive(Debug, Clone)]
+pub struct TerminationHandle {
+    requested: Arc<AtomicBool>,
+}
+
+impl TerminationHandle {
+    /// Aborts the script currently running in the context. The evaluation
+    /// fails with a terminated exception, which scripts cannot catch.
+    pub fn terminate(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Execution time limit and termination requests of a context.
+#[derive(Default)]
+pub(crate) struct ExecutionLimits {
+    time_limit: Cell<Option<Duration>>,
+    started: Cell<Option<Instant>>,
+    depth: Cell<usize>,
+    requested: Arc<AtomicBool>,
+    reason: Cell<Option<TerminationReason>>,
+}
+
+impl ExecutionLimits {
+    pub(crate) fn set_time_limit(&self, limit: Option<Duration>) {
+        self.time_limit.set(limit);
+    }
+
+    pub(crate) fn time_limit(&self) -> Option<Duration> {
+        self.time_limit.get()
+    }
+
+    pub(crate) fn termination_handle(&self) -> TerminationHandle {
+        TerminationHandle {
+            requested: self.requested.clone(),
+        }
+    }
+
+    /// Marks the start of an evaluation, or of a call into JavaScript from
+    /// Rust. Nested evaluations, started from callbacks, count towards the time
+    /// limit of the outermost one.
+    pub(crate) fn enter(&self) {
+        if self.depth.get() == 0 {
+            self.started.set(Some(Instant::now()));
+            self.requested.store(false, Ordering::SeqCst);
+            self.reason.set(None);
+        }
+        self.depth.set(self.depth.get() + 1);
+    }
+
+    /// Marks the end of an evaluation and returns why it was terminated, if it
+    /// was.
+    pub(crate) fn exit(&self) -> Option<TerminationReason> {
+        self.depth.set(self.depth.get() - 1);
+        if self.depth.get() > 0 {
+            return self.reason.get();
+        }
+        self.started.set(None);
+        self.requested.store(false, Ordering::SeqCst);
+        self.reason.take()
+    }
+
+    fn check(&self) -> Option<TerminationReason> {
+        // Once terminated, the script stays terminated until the outermost
+        // evaluation returns, even if a callback swallowed the exception.
+        if let Some(reason) = self.reason.get() {
+            return Some(reason);
+        }
+        if self.requested.load(Ordering::SeqCst) {
+            return Some(TerminationReason::Requested);
+        }
+        match (self.started.get(), self.time_limit.get()) {
+            (Some(started), Some(limit)) if started.elapsed() >= limit => {
+                Some(TerminationReason::TimedOut)
+            }
+            _ => None,
+        }
+    }
+
+    /// Records why the running script is terminated. A termination request is
+    /// consumed here, so that it can't outlive the script it aborted.
+    fn terminate(&self, reason: Option<TerminationReason>) -> bool {
+        if reason == Some(TerminationReason::Requested) {
+            self.requested.store(false, Ordering::SeqCst);
+        }
+        if reason.is_some() {
+            self.reason.set(reason);
+        }
+        reason.is_some()
+    }
+}
+
+/// Heap size limit of a context group.
+///
+/// The only heap size query JavaScriptCore offers builds a statistics object,
+/// which can't be done while checking the limit on every watchdog tick. The
+/// heap is measured when the outermost evaluation starts instead, and the
+/// growth of the memory of the process is added to it on every tick.
+#[derive(Default)]
+pub(crate) struct HeapLimit {
+    limit: Cell<Option<usize>>,
+    depth: Cell<usize>,
+    // The heap size and the resident memory of the process when the outermost
+    // evaluation started.
+    baseline: Cell<Option<(usize, usize)>>,
+}
+
+impl HeapLimit {
+    pub(crate) fn set(&self, limit: Option<usize>) {
+        self.limit.set(limit);
+    }
+
+    pub(crate) fn get(&self) -> Option<usize> {
+        self.limit.get()
+    }
+
+    /// Marks the start of an evaluation in a context of the group, measuring
+    /// the heap before the outermost one.
+    pub(crate) fn enter(&self, ctx: JSContextRef) {
+        if self.depth.get() == 0 && self.limit.get().is_some() {
+            self.baseline.set(resident_memory().map(|resident| (memory_size(ctx), resident)));
+        }
+        self.depth.set(self.depth.get() + 1);
+    }
+
+    /// Marks the end of an evaluation in a context of the group.
+    pub(crate) fn exit(&self) {
+        self.depth.set(self.depth.get() - 1);
+        if self.depth.get() == 0 {
+            self.baseline.set(None);
+        }
+    }
+
+    /// Called on every watchdog tick.
+    fn is_exceeded(&self, ctx: JSContextRef) -> bool {
+        let limit = match self.limit.get() {
+            Some(limit) => limit,
+            None => return false,
+        };
+        let size = match (self.baseline.get(), resident_memory()) {
+            (Some((heap, resident)), Some(now)) => heap + now.saturating_sub(resident),
+            // Where the resident memory can't be read, the heap is measured
+            // directly.
+            _ => memory_size(ctx),
+        };
+        size > limit
+    }
+}
+
+/// Returns the resident memory of the process, read without allocating.
+#[cfg(target_os = "linux")]
+fn resident_memory() -> Option<usize> {
+    use std::io::Read;
+
+    extern "C" {
+        fn sysconf(name: std::os::raw::c_int) -> std::os::raw::c_long;
+    }
+    const SC_PAGESIZE: std::os::raw::c_int = 30;
+
+    let mut buffer = [0u8; 128];
+    let length = std::fs::File::open("/proc/self/statm").ok()?.read(&mut buffer).ok()?;
+    let text = std::str::from_utf8(&buffer[..length]).ok()?;
+    let pages: usize = text.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { sysconf(SC_PAGESIZE) };
+    Some(pages * usize::try_from(page_size).ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn resident_memory() -> Option<usize> {
+    None
+}
+
+/// Returns the memory held by the heap of the context's group, including the
+/// memory outside of it that backs array buffers and typed arrays.
+fn memory_size(ctx: JSContextRef) -> usize {
+    let context = JSContext::from(ctx);
+    let statistics = unsafe { JSGetMemoryUsageStatistics(ctx) };
+    if statistics.is_null() {
+        return 0;
+    }
+    let statistics = JSObject::from(statistics);
+    let size = |name: &str| {
+        statistics
+            .get_property(&context, name)
+            .to_number(&context)
+            .map_or(0, |size| size as usize)
+    };
+    size("heapSize") + size("extraMemorySize")
+}
+
+unsafe extern "C" fn should_terminate(ctx: JSContextRef, _: *mut c_void) -> bool {
+    let state = match ContextState::lookup(JSContextGetGlobalContext(ctx)) {
+        Some(state) => state,
+        None => return false,
+    };
+    let reason = state.execution.check().or_else(|| {
+        let group = GroupState::lookup(JSContextGetGroup(ctx))?;
+        group.heap_limit.is_exceeded(ctx).then_some(TerminationReason::HeapLimitExceeded)
+    });
+    state.execution.terminate(reason)
+}
+
+/// Makes the scripts running in `group` periodically check the execution
+/// limits of their context and the heap limit of the group.
+pub(crate) fn install_watchdog(group: JSContextGroupRef) {
+    unsafe {
+        JSContextGroupSetExecutionTimeLimit(
+            group,
+            WATCHDOG_INTERVAL.as_secs_f64(),
+            Some(should_terminate),
+            std::ptr::null_mut(),
+        )
+    }
+}
@@ -2,6 +2,7 @@ use crate::internal::JSString;
 use rusty_jsc_sys::JSObjectCallAsFunctionCallback;
 use rusty_jsc_sys::*;
 
+use crate::js_callback::make_callback;
 use crate::js_context::JSContext;
 use crate::js_object::JSObject;
 use crate::js_exception::JSException;
@@ -56,6 +57,22 @@ impl JSValue {
         JSValue::from(func)
     }
 
+    /// Creates a function calling `callback`, with `name` and `length` as its
+    /// `name` and `length` properties, like a native function.
+    ///
+    /// Unlike the functions created by `callback`, it can also be invoked with
+    /// `new`; `NewTarget` tells the callback which way it was invoked. A
+    /// `#[callback]` function `foo` provides its metadata as `FOO_NAME` and
+    /// `FOO_LENGTH`, next to it.
+    pub fn callback_named(
+        context: &JSContext,
+        name: &str,
+        length: u32,
+        callback: JSObjectCallAsFunctionCallback,
+    ) -> JSValue {
+        make_callback(context, name, length, callback).into()
+    }
+
     pub fn from_json(context: &JSContext, json_string: String) -> Result<Self, JSException> {
         let value_ref = unsafe { JSValueMakeFromJSONString(context.inner(), JSString::from_utf8(json_string).inner) };
         if value_ref.is_null() {
@@ -94,6 +111,11 @@ impl JSValue {
         unsafe { JSValueIsString(context.inner(), self.inner) }
     }
 
+    /// Checks if this value is an object.
+    pub fn is_object(&self, context: &JSContext) -> bool {
+        unsafe { JSValueIsObject(context.inner(), self.inner) }
+    }
+
     /// Gets this value as a `bool`.
     pub fn to_bool(&self, context: &JSContext) -> bool {
         unsafe { JSValueToBoolean(context.inner(), self.inner) }
@@ -143,10 +165,18 @@ impl JSValue {
         let obj = JSString::from(string_ref);
         Ok(obj.to_string())
     }
-}
 
-unsafe impl Send for JSValue {}
-unsafe impl Sync for JSValue {}
+    /// Returns this value, created in `from`, for use in `to`.
+    ///
+    /// Values can only be shared between contexts of the same group, so this
+    /// fails if `from` and `to` belong to different groups.
+    pub fn transfer(&self, from: &JSContext, to: &JSContext) -> Result<JSValue, JSException> {
+        if !from.is_same_group(to) {
+            return Err(JSException::from("Can't transfer a value between contexts of different groups"));
+        }
+        Ok(self.clone())
+    }
+}
 
 impl From<JSValueRef> for JSValue {
     fn from(val: JSValueRef) -> Self {
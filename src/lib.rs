@@ -25,14 +25,19 @@
 
 mod internal;
 mod helpers;
+mod context_state;
+mod intrinsics;
+mod closure_function;
+mod macro_support;
 
 // #[macro_export]
 mod closure;
 pub use crate::internal::JSString;
-pub use rusty_jsc_macros::callback;
+pub use rusty_jsc_macros::{callback, constructor, js_methods, FromJs, IntoJs, JsClass};
 pub use rusty_jsc_sys::JSObjectCallAsFunctionCallback;
 pub mod private {
     pub use rusty_jsc_sys::*;
+    pub use crate::macro_support::*;
 }
 
 // pub use crate::closure::callback_closure;
@@ -40,16 +45,47 @@ pub mod private {
 mod js_context;
 pub use js_context::*;
 
+mod js_context_group;
+pub use js_context_group::*;
+
+mod js_heap_statistics;
+pub use js_heap_statistics::*;
+
+mod context_handle;
+pub use context_handle::{ContextClosed, ContextHandle};
+
 mod js_value;
 pub use js_value::*;
 
 mod js_object;
 pub use js_object::*;
 
+mod js_callback;
+pub use js_callback::{NewTarget, This};
+
+mod js_class;
+pub use js_class::*;
+
+mod js_convert;
+pub use js_convert::*;
+
+mod js_host_object;
+pub use js_host_object::*;
+
 mod js_promise;
 pub use js_promise::*;
 
 mod js_exception;
 pub use js_exception::*;
 
+mod js_script;
+pub use js_script::*;
+
+mod js_termination;
+pub use js_termination::{TerminationHandle, TerminationReason};
+
 mod js_protected_value;
+pub use js_protected_value::*;
+
+mod js_runtime_thread;
+pub use js_runtime_thread::*;
@@ -0,0 +1,223 @@
+//! Helpers used by the code generated by `rusty_jsc_macros`.
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
+
+use rusty_jsc_sys::*;
+
+use crate::js_class::{JsConvert, JsHasInstance};
+use crate::js_context::JSContext;
+use crate::js_convert::{FromJs, IntoJs};
+use crate::js_object::JSObject;
+use crate::js_value::JSValue;
+use crate::JSException;
+
+/// Collects the arguments passed to a callback.
+///
+/// # Safety
+///
+/// `arguments` must point to `count` values, as given by JavaScriptCore.
+pub unsafe fn arguments(count: size_t, arguments: *const JSValueRef) -> Vec<JSValue> {
+    if count == 0 {
+        return vec![];
+    }
+    std::slice::from_raw_parts(arguments, count as _)
+        .iter()
+        .map(|r| (*r).into())
+        .collect()
+}
+
+/// Converts the argument at `index`. Missing arguments are `undefined`.
+pub fn argument<T: FromJs>(
+    context: &JSContext,
+    arguments: &[JSValue],
+    index: usize,
+) -> Result<T, JSException> {
+    match arguments.get(index) {
+        Some(value) => T::from_js(context, value),
+        None => T::from_js(context, &JSValue::undefined(context)),
+    }
+}
+
+/// Converts the argument at `index`, or returns `default()` if it is missing or
+/// `undefined`.
+pub fn argument_or_else<T: FromJs>(
+    context: &JSContext,
+    arguments: &[JSValue],
+    index: usize,
+    default: impl FnOnce() -> T,
+) -> Result<T, JSException> {
+    match arguments.get(index) {
+        Some(value) if !value.is_undefined(context) => T::from_js(context, value),
+        _ => Ok(default()),
+    }
+}
+
+/// Converts the arguments from `index` on.
+pub fn rest<T: FromJs>(
+    context: &JSContext,
+    arguments: &[JSValue],
+    index: usize,
+) -> Result<Vec<T>, JSException> {
+    arguments
+        .iter()
+        .skip(index)
+        .map(|value| T::from_js(context, value))
+        .collect()
+}
+
+/// Throws a `TypeError` if fewer than `required` arguments were passed to the
+/// function called `name`.
+pub fn check_arguments(
+    context: &JSContext,
+    name: &str,
+    arguments: &[JSValue],
+    required: usize,
+) -> Result<(), JSException> {
+    if arguments.len() >= required {
+        return Ok(());
+    }
+    Err(JSException::type_error(
+        context,
+        format!(
+            "{} expects at least {} argument{}, but got {}",
+            name,
+            required,
+            if required == 1 { "" } else { "s" },
+            arguments.len()
+        ),
+    ))
+}
+
+/// Stores `error` in the exception out-parameter of a callback.
+///
+/// # Safety
+///
+/// `exception` must be null or valid for writes, as given by JavaScriptCore.
+pub unsafe fn throw(context: &JSContext, exception: *mut JSValueRef, error: JSException) {
+    if !exception.is_null() {
+        *exception = error.to_jsvalue(context).into();
+    }
+}
+
+/// Runs the body of a `#[callback]`, throwing the error it returns.
+///
+/// # Safety
+///
+/// `context` and `exception` must come from JavaScriptCore.
+pub unsafe fn run_callback<F>(context: JSContextRef, exception: *mut JSValueRef, callback: F) -> JSValueRef
+where
+    F: FnOnce(&JSContext) -> Result<JSValue, JSException>,
+{
+    let context = JSContext::from(context);
+    match callback(&context) {
+        Ok(value) => value.into(),
+        Err(err) => {
+            throw(&context, exception, err);
+            JSValue::undefined(&context).into()
+        }
+    }
+}
+
+/// Looks up the state a callback takes by reference.
+pub fn state<T: 'static>(context: &JSContext) -> Result<Rc<RefCell<T>>, JSException> {
+    context.user_data::<T>().ok_or_else(|| {
+        JSException::from(format!(
+            "State `{}` is not registered in the context",
+            std::any::type_name::<T>()
+        ))
+    })
+}
+
+pub fn borrow_state<T>(state: &RefCell<T>) -> Result<Ref<'_, T>, JSException> {
+    state.try_borrow().map_err(|_| in_use::<T>())
+}
+
+pub fn borrow_state_mut<T>(state: &RefCell<T>) -> Result<RefMut<'_, T>, JSException> {
+    state.try_borrow_mut().map_err(|_| in_use::<T>())
+}
+
+fn in_use<T>() -> JSException {
+    JSException::from(format!("State `{}` is already in use", std::any::type_name::<T>()))
+}
+
+/// The return value of a generated callback: either a value or a `Result`.
+pub trait IntoCallbackResult {
+    fn into_callback_result(self, context: &JSContext) -> Result<JSValue, JSException>;
+}
+
+impl<T: IntoJs> IntoCallbackResult for T {
+    fn into_callback_result(self, context: &JSContext) -> Result<JSValue, JSException> {
+        self.into_js(context)
+    }
+}
+
+impl<T: IntoJs> IntoCallbackResult for Result<T, JSException> {
+    fn into_callback_result(self, context: &JSContext) -> Result<JSValue, JSException> {
+        self?.into_js(context)
+    }
+}
+
+/// The `convertToType` callback of a `JsConvert` type.
+///
+/// # Safety
+///
+/// Only meant to be called by JavaScriptCore.
+pub unsafe extern "C" fn convert_to_type<T: JsConvert>(
+    ctx: JSContextRef,
+    object: JSObjectRef,
+    type_: JSType,
+    exception: *mut JSValueRef,
+) -> JSValueRef {
+    let context = JSContext::from(ctx);
+    let object: JSObject = object.into();
+    let value = match object.downcast_ref::<T>(&context) {
+        Some(value) => value,
+        None => return std::ptr::null(),
+    };
+    let result = if type_ == JSType_kJSTypeNumber {
+        value
+            .convert_to_number(&context)
+            .map(|number| number.map(|number| JSValue::number(&context, number)))
+    } else if type_ == JSType_kJSTypeString {
+        value
+            .convert_to_string(&context)
+            .map(|string| string.map(|string| JSValue::string(&context, string)))
+    } else {
+        Ok(None)
+    };
+    match result {
+        Ok(Some(value)) => value.into(),
+        Ok(None) => std::ptr::null(),
+        Err(err) => {
+            throw(&context, exception, err);
+            std::ptr::null()
+        }
+    }
+}
+
+/// The `hasInstance` callback of a `JsHasInstance` type.
+///
+/// # Safety
+///
+/// Only meant to be called by JavaScriptCore.
+pub unsafe extern "C" fn has_instance<T: JsHasInstance>(
+    ctx: JSContextRef,
+    constructor: JSObjectRef,
+    possible_instance: JSValueRef,
+    exception: *mut JSValueRef,
+) -> bool {
+    let context = JSContext::from(ctx);
+    let constructor: JSObject = constructor.into();
+    let value = match constructor.downcast_ref::<T>(&context) {
+        Some(value) => value,
+        None => return false,
+    };
+    match value.has_instance(&context, &possible_instance.into()) {
+        Ok(result) => result,
+        Err(err) => {
+            throw(&context, exception, err);
+            false
+        }
+    }
+}
@@ -46,6 +46,17 @@ pub struct OpaqueJSPropertyNameAccumulator {
 
 pub type JSPropertyNameAccumulatorRef = *mut OpaqueJSPropertyNameAccumulator;
 
+#[repr(C)]
+pub struct OpaqueJSWeakObjectMap {
+    _unused: [u8; 0],
+}
+
+pub type JSWeakObjectMapRef = *mut OpaqueJSWeakObjectMap;
+
+pub type JSWeakMapDestroyedCallback = ::std::option::Option<
+    unsafe extern "C" fn(map: JSWeakObjectMapRef, data: *mut ::std::os::raw::c_void),
+>;
+
 pub type JSTypedArrayBytesDeallocator = ::std::option::Option<
     unsafe extern "C" fn(
         bytes: *mut ::std::os::raw::c_void,
@@ -84,6 +95,22 @@ extern "C" {
 
     pub fn JSGarbageCollect(ctx: JSContextRef);
 }
+extern "C" {
+    // From JSBasePrivate.h.
+
+    pub fn JSReportExtraMemoryCost(ctx: JSContextRef, size: size_t);
+    pub fn JSSynchronousGarbageCollectForDebugging(ctx: JSContextRef);
+    pub fn JSGetMemoryUsageStatistics(ctx: JSContextRef) -> JSObjectRef;
+}
+extern "C" {
+    // From JSWeakObjectMapRefPrivate.h.
+
+    pub fn JSWeakObjectMapCreate(
+        ctx: JSContextRef,
+        data: *mut ::std::os::raw::c_void,
+        destructor: JSWeakMapDestroyedCallback,
+    ) -> JSWeakObjectMapRef;
+}
 pub const JSType_kJSTypeUndefined: JSType = 0;
 pub const JSType_kJSTypeNull: JSType = 1;
 pub const JSType_kJSTypeBoolean: JSType = 2;
@@ -937,6 +964,59 @@ extern "C" {
     pub fn JSGlobalContextSetName(ctx: JSGlobalContextRef, name: JSStringRef);
 }
 
+pub type JSShouldTerminateCallback = ::std::option::Option<
+    unsafe extern "C" fn(ctx: JSContextRef, context: *mut ::std::os::raw::c_void) -> bool,
+>;
+
+extern "C" {
+    // From JSContextRefPrivate.h.
+
+    pub fn JSContextGroupSetExecutionTimeLimit(
+        group: JSContextGroupRef,
+        limit: f64,
+        callback: JSShouldTerminateCallback,
+        context: *mut ::std::os::raw::c_void,
+    );
+    pub fn JSContextGroupClearExecutionTimeLimit(group: JSContextGroupRef);
+}
+
+#[repr(C)]
+pub struct OpaqueJSScript {
+    _unused: [u8; 0],
+}
+
+pub type JSScriptRef = *mut OpaqueJSScript;
+
+extern "C" {
+    // From JSScriptRefPrivate.h.
+
+    pub fn JSScriptCreateReferencingImmortalASCIIText(
+        contextGroup: JSContextGroupRef,
+        url: JSStringRef,
+        startingLineNumber: ::std::os::raw::c_int,
+        source: *const ::std::os::raw::c_char,
+        length: size_t,
+        errorMessage: *mut JSStringRef,
+        errorLine: *mut ::std::os::raw::c_int,
+    ) -> JSScriptRef;
+    pub fn JSScriptCreateFromString(
+        contextGroup: JSContextGroupRef,
+        url: JSStringRef,
+        startingLineNumber: ::std::os::raw::c_int,
+        source: JSStringRef,
+        errorMessage: *mut JSStringRef,
+        errorLine: *mut ::std::os::raw::c_int,
+    ) -> JSScriptRef;
+    pub fn JSScriptRetain(script: JSScriptRef);
+    pub fn JSScriptRelease(script: JSScriptRef);
+    pub fn JSScriptEvaluate(
+        context: JSContextRef,
+        script: JSScriptRef,
+        thisValue: JSValueRef,
+        exception: *mut JSValueRef,
+    ) -> JSValueRef;
+}
+
 pub type JSChar = ::std::os::raw::c_ushort;
 
 extern "C" {
@@ -0,0 +1,74 @@
+use rusty_jsc::{callback, JSContext, JSException, JSObject, JSValue, NewTarget};
+
+#[callback(name = "readFile", length = 2)]
+fn read_file(path: String, _encoding: Option<String>) -> String {
+    format!("contents of {}", path)
+}
+
+#[callback]
+fn generic<T: Default + Into<f64>>(_optional: Option<f64>) -> f64 {
+    T::default().into()
+}
+
+struct Clock;
+
+impl Clock {
+    /// Returns a string when called, and sets `seconds` on the new object with
+    /// `new`.
+    #[callback(name = "Timestamp")]
+    fn timestamp(ctx: JSContext, this: JSObject, new_target: NewTarget) -> Result<JSValue, JSException> {
+        if !new_target.is_constructor_call() {
+            return Ok(JSValue::string(&ctx, "1970-01-01T00:00:00Z"));
+        }
+        this.set_property(&ctx, "seconds", JSValue::number(&ctx, 0.0))?;
+        Ok(JSValue::undefined(&ctx))
+    }
+}
+
+fn eval(context: &JSContext, script: &str) -> String {
+    context
+        .evaluate_script(script, 1)
+        .unwrap()
+        .to_string(context)
+        .unwrap()
+        .to_string()
+}
+
+fn set_global(context: &JSContext, name: &str, value: JSValue) {
+    context.get_global_object().set_property(context, name, value).unwrap();
+}
+
+#[test]
+fn callbacks_provide_their_name_and_length() {
+    assert_eq!((READ_FILE_NAME, READ_FILE_LENGTH), ("readFile", 2));
+    assert_eq!((GENERIC_NAME, GENERIC_LENGTH), ("generic", 0));
+
+    let context = JSContext::default();
+    let read_file = JSValue::callback_named(&context, READ_FILE_NAME, READ_FILE_LENGTH, Some(read_file));
+    set_global(&context, "f", read_file);
+    assert_eq!(eval(&context, "[f.name, f.length, f('notes.txt')].join()"), "readFile,2,contents of notes.txt");
+}
+
+#[test]
+fn callbacks_in_impl_blocks_provide_associated_consts() {
+    let context = JSContext::default();
+    let timestamp = JSValue::callback_named(
+        &context,
+        Clock::TIMESTAMP_NAME,
+        Clock::TIMESTAMP_LENGTH,
+        Some(Clock::timestamp),
+    );
+    set_global(&context, Clock::TIMESTAMP_NAME, timestamp);
+    assert_eq!(
+        eval(&context, "const t = new Timestamp(); [Timestamp.name, Timestamp(), t instanceof Timestamp, t.seconds].join()"),
+        "Timestamp,1970-01-01T00:00:00Z,true,0"
+    );
+}
+
+#[test]
+fn plain_callbacks_are_unchanged() {
+    let context = JSContext::default();
+    set_global(&context, "f", JSValue::callback(&context, Some(read_file)));
+    assert_eq!(eval(&context, "[f.name, f('a')].join()"), ",contents of a");
+    assert!(context.evaluate_script("new f('a')", 1).is_err());
+}
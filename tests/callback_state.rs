@@ -0,0 +1,71 @@
+use rusty_jsc::{callback, JSContext, JSException, JSObject, JSValue};
+
+struct Log(Vec<String>);
+
+#[callback]
+fn record(#[state] log: &mut Log, entry: String) -> usize {
+    log.0.push(entry);
+    log.0.len()
+}
+
+#[callback]
+fn count(#[state] log: &Log) -> usize {
+    log.0.len()
+}
+
+/// Calls `f` while holding the log.
+#[callback]
+fn with_log(ctx: &JSContext, #[state] _log: &mut Log, f: JSObject) -> Result<JSValue, JSException> {
+    f.call(ctx, None, &[])
+}
+
+fn context() -> JSContext {
+    let context = JSContext::default();
+    context.set_user_data(Log(vec![]));
+    let global = context.get_global_object();
+    for (name, callback) in [
+        ("record", JSValue::callback(&context, Some(record))),
+        ("count", JSValue::callback(&context, Some(count))),
+        ("withLog", JSValue::callback(&context, Some(with_log))),
+    ] {
+        global.set_property(&context, name, callback).unwrap();
+    }
+    context
+}
+
+#[test]
+fn state_is_borrowed_from_user_data() {
+    let context = context();
+    let result = context.evaluate_script("record('a'); record('b'); count()", 1).unwrap();
+    assert_eq!(result.to_number(&context).unwrap(), 2.0);
+    assert_eq!(context.user_data::<Log>().unwrap().borrow().0, ["a", "b"]);
+}
+
+#[test]
+fn arguments_are_converted_before_the_state_is_borrowed() {
+    let context = context();
+    let result = context
+        .evaluate_script("record({ toString() { record('inner'); return 'outer'; } })", 1)
+        .unwrap();
+    assert_eq!(result.to_number(&context).unwrap(), 2.0);
+    assert_eq!(context.user_data::<Log>().unwrap().borrow().0, ["inner", "outer"]);
+}
+
+#[test]
+fn state_in_use_by_the_caller_throws() {
+    let context = context();
+    let error = context.evaluate_script("withLog(() => count())", 1).unwrap_err();
+    assert!(error.to_string().contains("is already in use"), "{}", error);
+    // The borrow ended with the call.
+    let result = context.evaluate_script("count()", 1).unwrap();
+    assert_eq!(result.to_number(&context).unwrap(), 0.0);
+}
+
+#[test]
+fn missing_state_throws() {
+    let context = JSContext::default();
+    let count = JSValue::callback(&context, Some(count));
+    context.get_global_object().set_property(&context, "count", count).unwrap();
+    let error = context.evaluate_script("count()", 1).unwrap_err();
+    assert!(error.to_string().contains("is not registered"), "{}", error);
+}
@@ -0,0 +1,192 @@
+use rusty_jsc::{FromJs, IntoJs, JSContext, JSValue};
+
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+#[js(rename_all = "camelCase")]
+struct Settings {
+    user_name: String,
+    retry_count: u32,
+    dark_mode: Option<bool>,
+    #[js(rename = "lang")]
+    language: String,
+    #[js(default)]
+    tags: Vec<String>,
+    #[js(skip)]
+    cached: bool,
+}
+
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+enum Level {
+    #[js(rename = "low")]
+    Low,
+    High,
+}
+
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+#[js(tag = "type", rename_all = "kebab-case")]
+enum Shape {
+    Circle { radius_px: f64 },
+    #[js(rename_all = "camelCase")]
+    RoundedRect { corner_radius: f64, level: Level },
+    Empty,
+}
+
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+struct Meters(f64);
+
+/// Fields named like the bindings of the generated code.
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+struct Binding {
+    context: String,
+    value: f64,
+}
+
+#[derive(Debug, PartialEq, FromJs, IntoJs)]
+#[js(tag = "kind")]
+enum Event {
+    Set { context: String, value: f64 },
+}
+
+fn json(context: &JSContext, value: JSValue) -> String {
+    let global = context.get_global_object();
+    global.set_property(context, "value", value).unwrap();
+    context
+        .evaluate_script("JSON.stringify(value)", 1)
+        .unwrap()
+        .to_string(context)
+        .unwrap()
+        .to_string()
+}
+
+fn eval(context: &JSContext, script: &str) -> JSValue {
+    context.evaluate_script(script, 1).unwrap()
+}
+
+fn round_trip<T: FromJs + IntoJs + PartialEq + std::fmt::Debug>(context: &JSContext, value: T) -> T {
+    let js = value.into_js(context).unwrap();
+    T::from_js(context, &js).unwrap()
+}
+
+#[test]
+fn structs_round_trip_with_renamed_fields() {
+    let context = JSContext::default();
+    let settings = Settings {
+        user_name: "ada".to_string(),
+        retry_count: 3,
+        dark_mode: None,
+        language: "en".to_string(),
+        tags: vec!["a".to_string(), "b".to_string()],
+        cached: true,
+    };
+    let js = json(&context, settings.into_js(&context).unwrap());
+    assert_eq!(js, r#"{"userName":"ada","retryCount":3,"lang":"en","tags":["a","b"]}"#);
+
+    let settings = Settings::from_js(&context, &eval(&context, "({ userName: 'bob', retryCount: 1, darkMode: true, lang: 'fr' })")).unwrap();
+    assert_eq!(
+        settings,
+        Settings {
+            user_name: "bob".to_string(),
+            retry_count: 1,
+            dark_mode: Some(true),
+            language: "fr".to_string(),
+            tags: vec![],
+            cached: false,
+        }
+    );
+    let cached = Settings { cached: true, ..settings };
+    assert!(!round_trip(&context, cached).cached);
+}
+
+#[test]
+fn missing_and_mistyped_properties_are_errors() {
+    let context = JSContext::default();
+    let error = Settings::from_js(&context, &eval(&context, "({ userName: 'bob', retryCount: 1 })")).unwrap_err();
+    assert!(error.to_string().contains("Missing property `lang`"), "{}", error);
+    assert!(Settings::from_js(&context, &eval(&context, "'settings'")).is_err());
+    assert!(Level::from_js(&context, &eval(&context, "'medium'")).is_err());
+    assert!(Shape::from_js(&context, &eval(&context, "({ type: 'triangle' })")).is_err());
+}
+
+#[test]
+fn unit_enums_round_trip_as_strings() {
+    let context = JSContext::default();
+    assert_eq!(json(&context, Level::Low.into_js(&context).unwrap()), r#""low""#);
+    assert_eq!(json(&context, Level::High.into_js(&context).unwrap()), r#""High""#);
+    assert_eq!(round_trip(&context, Level::Low), Level::Low);
+    assert_eq!(round_trip(&context, Level::High), Level::High);
+}
+
+#[test]
+fn rename_all_on_a_tagged_enum_renames_the_variants_only() {
+    let context = JSContext::default();
+    let circle = Shape::Circle { radius_px: 2.0 };
+    assert_eq!(
+        json(&context, circle.into_js(&context).unwrap()),
+        r#"{"type":"circle","radius_px":2}"#
+    );
+    let rect = Shape::RoundedRect {
+        corner_radius: 4.0,
+        level: Level::Low,
+    };
+    assert_eq!(
+        json(&context, rect.into_js(&context).unwrap()),
+        r#"{"type":"rounded-rect","cornerRadius":4,"level":"low"}"#
+    );
+    assert_eq!(json(&context, Shape::Empty.into_js(&context).unwrap()), r#"{"type":"empty"}"#);
+
+    for shape in [
+        Shape::Circle { radius_px: 1.5 },
+        Shape::RoundedRect {
+            corner_radius: 3.0,
+            level: Level::High,
+        },
+        Shape::Empty,
+    ] {
+        let debug = format!("{:?}", shape);
+        assert_eq!(format!("{:?}", round_trip(&context, shape)), debug);
+    }
+}
+
+#[test]
+fn newtypes_and_collections_round_trip() {
+    let context = JSContext::default();
+    assert_eq!(json(&context, Meters(1.5).into_js(&context).unwrap()), "1.5");
+    assert_eq!(round_trip(&context, Meters(1.5)), Meters(1.5));
+    assert_eq!(round_trip(&context, vec![Some(1), None, Some(3)]), vec![Some(1), None, Some(3)]);
+    assert_eq!(round_trip(&context, "text".to_string()), "text");
+    assert!(round_trip(&context, true));
+}
+
+#[test]
+fn fields_named_like_generated_bindings() {
+    let context = JSContext::default();
+    let binding = Binding {
+        context: "global".to_string(),
+        value: 2.0,
+    };
+    assert_eq!(
+        json(&context, binding.into_js(&context).unwrap()),
+        r#"{"context":"global","value":2}"#
+    );
+    let binding = Binding::from_js(&context, &eval(&context, "({ context: 'local', value: 3 })")).unwrap();
+    assert_eq!(
+        binding,
+        Binding {
+            context: "local".to_string(),
+            value: 3.0,
+        }
+    );
+
+    let event = Event::Set {
+        context: "global".to_string(),
+        value: 1.0,
+    };
+    assert_eq!(
+        json(&context, event.into_js(&context).unwrap()),
+        r#"{"kind":"Set","context":"global","value":1}"#
+    );
+    let event = Event::Set {
+        context: "local".to_string(),
+        value: 4.0,
+    };
+    assert_eq!(round_trip(&context, event), Event::Set { context: "local".to_string(), value: 4.0 });
+}
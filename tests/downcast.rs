@@ -0,0 +1,113 @@
+use rusty_jsc::{constructor, JSClass, JSContext, JSException, JSObject, JSValue};
+
+struct Point(f64);
+
+#[constructor]
+fn point(ctx: JSContext, _constructor: JSObject, args: &[JSValue]) -> Result<Point, JSException> {
+    let x = match args.first() {
+        Some(x) => x.to_number(&ctx)?,
+        None => 0.0,
+    };
+    Ok(Point(x))
+}
+
+fn object(context: &JSContext, script: &str) -> JSObject {
+    context
+        .evaluate_script(script, 1)
+        .unwrap()
+        .to_object(context)
+        .unwrap()
+}
+
+fn context_with_point() -> JSContext {
+    let context = JSContext::default();
+    let class = JSClass::builder("Point").build();
+    let constructor = JSObject::new_constructor(&context, &class, Some(point));
+    context
+        .get_global_object()
+        .set_property(&context, "Point", constructor.to_jsvalue())
+        .unwrap();
+    context
+}
+
+#[test]
+fn downcast_ref_of_an_instance() {
+    let context = context_with_point();
+    let point = object(&context, "new Point(3)");
+    assert_eq!(point.downcast_ref::<Point>(&context).unwrap().0, 3.0);
+    assert!(point.downcast_ref::<String>(&context).is_none());
+}
+
+#[test]
+fn downcast_ref_ignores_objects_inheriting_from_an_instance() {
+    let context = context_with_point();
+    let forged = object(&context, "Object.create(new Point(3))");
+    assert!(forged.downcast_ref::<Point>(&context).is_none());
+    let forged = object(&context, "Object.setPrototypeOf({}, Point.prototype)");
+    assert!(forged.downcast_ref::<Point>(&context).is_none());
+}
+
+#[test]
+fn downcast_ref_ignores_script_objects_and_prototypes() {
+    let context = context_with_point();
+    for script in ["({})", "[1, 2]", "(function () {})", "Point", "Point.prototype"] {
+        let value = object(&context, script);
+        assert!(value.downcast_ref::<Point>(&context).is_none(), "{}", script);
+    }
+}
+
+#[test]
+fn new_instance_rejects_constructors_from_scripts() {
+    let context = context_with_point();
+    let class = JSClass::builder("Secret").build();
+    let secret = JSObject::new_with_data(&context, &class, String::from("secret"));
+    context
+        .get_global_object()
+        .set_property(&context, "secret", secret.to_jsvalue())
+        .unwrap();
+    for script in [
+        "(function () {})",
+        "(function () { function F() {} F.prototype = secret; return F; })()",
+        "(function () { function F() {} F.prototype = Point.prototype; return F; })()",
+        "Point",
+    ] {
+        let constructor = object(&context, script);
+        assert!(JSObject::new_instance(&context, &constructor, Point(1.0)).is_err(), "{}", script);
+    }
+    assert_eq!(secret.downcast_ref::<String>(&context).unwrap(), "secret");
+}
+
+#[test]
+fn instances_of_subclasses_carry_the_data() {
+    let context = context_with_point();
+    let point = object(
+        &context,
+        "class Point3 extends Point { constructor() { super(5); } }; globalThis.p = new Point3(); p",
+    );
+    assert_eq!(point.downcast_ref::<Point>(&context).unwrap().0, 5.0);
+    let inherits = context
+        .evaluate_script("Object.getPrototypeOf(p) === Point3.prototype", 1)
+        .unwrap();
+    assert!(inherits.to_bool(&context));
+}
+
+#[test]
+fn constructors_ignore_builtins_replaced_by_scripts() {
+    let context = JSContext::default();
+    context
+        .evaluate_script(
+            "Reflect.construct = () => ({ forged: true }); \
+             Object.setPrototypeOf = () => { throw new Error('hijacked'); }; \
+             Object.defineProperty = () => { throw new Error('hijacked'); };",
+            1,
+        )
+        .unwrap();
+    let class = JSClass::builder("Point").build();
+    let constructor = JSObject::new_constructor(&context, &class, Some(point));
+    context
+        .get_global_object()
+        .set_property(&context, "Point", constructor.to_jsvalue())
+        .unwrap();
+    let point = object(&context, "class Point3 extends Point { constructor() { super(7); } }; new Point3()");
+    assert_eq!(point.downcast_ref::<Point>(&context).unwrap().0, 7.0);
+}
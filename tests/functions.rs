@@ -0,0 +1,118 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rusty_jsc::private::kJSPropertyAttributeNone;
+use rusty_jsc::{JSClass, JSContext, JSObject, JSValue};
+
+const HIJACK: &str = "\
+    Object.defineProperty = () => { throw new Error('hijacked'); }; \
+    Object.prototype.value = 'poisoned'; \
+    Function = function () {}; \
+";
+
+fn eval(context: &JSContext, script: &str) -> JSValue {
+    context.evaluate_script(script, 1).unwrap()
+}
+
+fn set_global(context: &JSContext, name: &str, value: JSValue) {
+    context.get_global_object().set_property(context, name, value).unwrap();
+}
+
+#[test]
+fn closure_functions_behave_like_native_functions() {
+    let context = JSContext::default();
+    let add = JSObject::new_function_with_closure(&context, "add", |ctx, _this, args| {
+        let sum = args.iter().map(|arg| arg.to_number(ctx)).sum::<Result<f64, _>>()?;
+        Ok(JSValue::number(ctx, sum))
+    });
+    set_global(&context, "add", add.into());
+    let result = eval(
+        &context,
+        "[add.name, add.length, add.call(null, 1, 2), add.apply(null, [3, 4]), add.bind(null, 5)(6), add instanceof Function].join()",
+    );
+    assert_eq!(result.to_string(&context).unwrap().to_string(), "add,0,3,7,11,true");
+}
+
+#[test]
+fn class_members_installed_after_scripts_ran_ignore_replaced_builtins() {
+    let context = JSContext::default();
+    eval(&context, HIJACK);
+    let class = JSClass::builder("Thing")
+        .static_function_with_closure("describe", |ctx, _this, _args| Ok(JSValue::string(ctx, "a thing")), kJSPropertyAttributeNone)
+        .static_value_with_closure("size", |ctx, _this| Ok(JSValue::number(ctx, 3.0)), kJSPropertyAttributeNone)
+        .build();
+    set_global(&context, "thing", JSObject::new_with_data(&context, &class, ()).into());
+    let result = eval(
+        &context,
+        "const inherits = Object.getPrototypeOf(thing.describe) === Object.getPrototypeOf(() => {}); \
+         [thing.describe.call(thing), thing.size, inherits].join()",
+    );
+    assert_eq!(result.to_string(&context).unwrap().to_string(), "a thing,3,true");
+}
+
+#[test]
+fn class_members_survive_a_garbage_collection_without_instances() {
+    let context = JSContext::default();
+    let class = JSClass::builder("Thing")
+        .static_function_with_closure("describe", |ctx, _this, _args| Ok(JSValue::string(ctx, "a thing")), kJSPropertyAttributeNone)
+        .build();
+    let describe = || {
+        set_global(&context, "thing", JSObject::new_with_data(&context, &class, ()).into());
+        let result = eval(&context, "(() => { const described = thing.describe(); delete globalThis.thing; return described; })()");
+        result.to_string(&context).unwrap().to_string()
+    };
+    assert_eq!(describe(), "a thing");
+    // No instance keeps the prototype alive anymore.
+    context.garbage_collect_sync();
+    assert_eq!(describe(), "a thing");
+}
+
+#[test]
+fn accessors_ignore_builtins_replaced_by_scripts() {
+    let context = JSContext::default();
+    eval(&context, HIJACK);
+    let stored = Rc::new(Cell::new(1.0));
+    let object = JSObject::new(&context);
+    object
+        .define_accessor(
+            &context,
+            "count",
+            {
+                let stored = stored.clone();
+                move |ctx, _this| Ok(JSValue::number(ctx, stored.get()))
+            },
+            {
+                let stored = stored.clone();
+                move |ctx, _this, value| {
+                    stored.set(value.to_number(ctx)?);
+                    Ok(())
+                }
+            },
+            kJSPropertyAttributeNone,
+        )
+        .unwrap();
+    set_global(&context, "object", object.into());
+
+    let result = eval(
+        &context,
+        "object.count += 41; \
+         const { get, set } = Reflect.getOwnPropertyDescriptor(object, 'count'); \
+         const inherits = Object.getPrototypeOf(get) === Object.getPrototypeOf(() => {}); \
+         [object.count, get.name, set.name, set.length, inherits].join()",
+    );
+    assert_eq!(result.to_string(&context).unwrap().to_string(), "42,get count,set count,1,true");
+    assert_eq!(stored.get(), 42.0);
+}
+
+#[test]
+fn getters_ignore_builtins_replaced_by_scripts() {
+    let context = JSContext::default();
+    eval(&context, HIJACK);
+    let object = JSObject::new(&context);
+    object
+        .define_getter(&context, "answer", |ctx, _this| Ok(JSValue::number(ctx, 42.0)), kJSPropertyAttributeNone)
+        .unwrap();
+    set_global(&context, "object", object.into());
+    let result = eval(&context, "'use strict'; try { object.answer = 1; } catch (e) { } object.answer");
+    assert_eq!(result.to_number(&context).unwrap(), 42.0);
+}
@@ -0,0 +1,47 @@
+use rusty_jsc::{JSContextGroup, TerminationReason};
+
+const LIMIT: usize = 64 * 1024 * 1024;
+
+#[test]
+fn array_buffers_count_towards_the_heap_limit() {
+    let group = JSContextGroup::new();
+    group.set_heap_limit(Some(LIMIT));
+    let context = group.new_context();
+    let error = context
+        .evaluate_script(
+            "const buffers = []; for (;;) { buffers.push(new Uint8Array(1024 * 1024).fill(1)); }",
+            1,
+        )
+        .unwrap_err();
+    assert_eq!(error.termination(), Some(TerminationReason::HeapLimitExceeded));
+}
+
+#[test]
+fn scripts_allocating_quickly_are_stopped_near_the_limit() {
+    let group = JSContextGroup::new();
+    group.set_heap_limit(Some(LIMIT));
+    let context = group.new_context();
+    let error = context
+        .evaluate_script(
+            "var chunks = []; for (;;) { chunks.push(new Uint8Array(16 * 1024 * 1024).fill(1)); }",
+            1,
+        )
+        .unwrap_err();
+    assert_eq!(error.termination(), Some(TerminationReason::HeapLimitExceeded));
+    let allocated = context.evaluate_script("chunks.length * 16 * 1024 * 1024", 1).unwrap();
+    assert!(allocated.to_number(&context).unwrap() < (4 * LIMIT) as f64);
+}
+
+#[test]
+fn scripts_under_the_heap_limit_run_to_completion() {
+    let group = JSContextGroup::new();
+    group.set_heap_limit(Some(LIMIT));
+    let context = group.new_context();
+    let value = context
+        .evaluate_script(
+            "let total = 0; for (let i = 0; i < 1000; i++) { total += new ArrayBuffer(1024).byteLength; } total",
+            1,
+        )
+        .unwrap();
+    assert_eq!(value.to_number(&context).unwrap(), 1024.0 * 1000.0);
+}
@@ -0,0 +1,110 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use rusty_jsc::{HostObject, JSContext, JSException, JSValue};
+
+/// Fails on every property starting with `bad`, and counts the reads.
+#[derive(Default)]
+struct Store {
+    values: RefCell<BTreeMap<String, f64>>,
+    reads: Rc<Cell<u32>>,
+}
+
+impl HostObject for Store {
+    fn get(&self, context: &JSContext, name: &str) -> Result<Option<JSValue>, JSException> {
+        self.reads.set(self.reads.get() + 1);
+        if name.starts_with("bad") {
+            return Err(JSException::type_error(context, format!("Can't read `{}`", name)));
+        }
+        Ok(self.values.borrow().get(name).map(|value| JSValue::number(context, *value)))
+    }
+
+    fn set(&self, context: &JSContext, name: &str, value: JSValue) -> Result<bool, JSException> {
+        if name.starts_with("bad") {
+            return Err(JSException::type_error(context, format!("Can't write `{}`", name)));
+        }
+        self.values.borrow_mut().insert(name.to_string(), value.to_number(context)?);
+        Ok(true)
+    }
+
+    fn delete(&self, context: &JSContext, name: &str) -> Result<bool, JSException> {
+        if name.starts_with("bad") {
+            return Err(JSException::type_error(context, format!("Can't delete `{}`", name)));
+        }
+        Ok(self.values.borrow_mut().remove(name).is_some())
+    }
+}
+
+/// Answers `in` without computing the values.
+struct Everything;
+
+impl HostObject for Everything {
+    const IMPLEMENTS_HAS: bool = true;
+
+    fn get(&self, _context: &JSContext, _name: &str) -> Result<Option<JSValue>, JSException> {
+        Ok(None)
+    }
+
+    fn has(&self, _context: &JSContext, _name: &str) -> bool {
+        true
+    }
+}
+
+fn context_with<T: HostObject>(host: T) -> JSContext {
+    let context = JSContext::default();
+    context
+        .get_global_object()
+        .set_property(&context, "host", host.into_object(&context).to_jsvalue())
+        .unwrap();
+    context
+}
+
+fn error_message(context: &JSContext, script: &str) -> String {
+    context.evaluate_script(script, 1).unwrap_err().to_string()
+}
+
+#[test]
+fn errors_from_get_set_and_delete_are_thrown() {
+    let context = context_with(Store::default());
+    assert!(error_message(&context, "host.badRead").contains("Can't read `badRead`"));
+    assert!(error_message(&context, "host.badWrite = 1").contains("Can't write `badWrite`"));
+    assert!(error_message(&context, "delete host.badDelete").contains("Can't delete `badDelete`"));
+    let caught = context
+        .evaluate_script("try { host.badRead; 'no error' } catch (e) { e instanceof TypeError }", 1)
+        .unwrap();
+    assert!(caught.to_bool(&context));
+}
+
+#[test]
+fn in_operator_reads_each_property_once_and_throws_errors() {
+    let reads = Rc::new(Cell::new(0));
+    let context = context_with(Store {
+        reads: reads.clone(),
+        ..Store::default()
+    });
+    let result = context
+        .evaluate_script("host.count = 2; ['count' in host, 'missing' in host]", 1)
+        .unwrap()
+        .to_object(&context)
+        .unwrap();
+    assert!(result.get_property_at_index(&context, 0).unwrap().to_bool(&context));
+    assert!(!result.get_property_at_index(&context, 1).unwrap().to_bool(&context));
+
+    reads.set(0);
+    context.evaluate_script("'count' in host", 1).unwrap();
+    assert_eq!(reads.get(), 1);
+    assert!(error_message(&context, "'badRead' in host").contains("Can't read `badRead`"));
+}
+
+#[test]
+fn custom_has_is_used_for_the_in_operator() {
+    let context = context_with(Everything);
+    let result = context
+        .evaluate_script("['anything' in host, host.anything]", 1)
+        .unwrap()
+        .to_object(&context)
+        .unwrap();
+    assert!(result.get_property_at_index(&context, 0).unwrap().to_bool(&context));
+    assert!(result.get_property_at_index(&context, 1).unwrap().is_undefined(&context));
+}
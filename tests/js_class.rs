@@ -0,0 +1,47 @@
+use std::cell::RefCell;
+
+use rusty_jsc::{js_methods, JSContext, JSException, JsClass};
+
+#[derive(JsClass)]
+struct Log {
+    #[js(get, set)]
+    lines: RefCell<Vec<String>>,
+}
+
+#[js_methods]
+impl Log {
+    /// Runs `script` while the lines are borrowed mutably.
+    fn append(&self, ctx: &JSContext, script: String) -> Result<String, JSException> {
+        let mut lines = self.lines.borrow_mut();
+        let line = ctx.evaluate_script(&script, 1)?.to_string(ctx)?.to_string();
+        lines.push(line.clone());
+        Ok(line)
+    }
+}
+
+#[test]
+fn reading_a_borrowed_ref_cell_property_throws() {
+    let context = JSContext::default();
+    let log = Log {
+        lines: RefCell::new(vec!["start".to_string()]),
+    };
+    context
+        .get_global_object()
+        .set_property(&context, "log", log.into_object(&context).to_jsvalue())
+        .unwrap();
+
+    let line = context
+        .evaluate_script("log.append(\"try { log.lines } catch (e) { e }\")", 1)
+        .unwrap()
+        .to_string(&context)
+        .unwrap()
+        .to_string();
+    assert_eq!(line, "Can't read a property that is being modified");
+    let lines = context
+        .evaluate_script("log.lines.join()", 1)
+        .unwrap()
+        .to_string(&context)
+        .unwrap()
+        .to_string();
+    assert_eq!(lines, "start,Can't read a property that is being modified");
+}
@@ -0,0 +1,22 @@
+use std::thread;
+
+use rusty_jsc::{JSContext, JSObject, Persistent};
+
+#[test]
+fn handles_dropped_on_other_threads_are_released_on_the_owning_thread() {
+    let context = JSContext::default();
+    let object = Persistent::new(&context, JSObject::new(&context));
+    let copy = object.clone();
+    thread::spawn(move || {
+        assert!(object.try_get().is_none());
+        drop(object);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(context.run_pending_tasks(), 1);
+    assert!(copy.get().to_jsvalue().is_object(&context));
+
+    // Dropping on the owning thread releases the value right away.
+    drop(copy);
+    assert_eq!(context.run_pending_tasks(), 0);
+}
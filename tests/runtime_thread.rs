@@ -0,0 +1,124 @@
+use std::future::Future;
+use std::pin::pin;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+use rusty_jsc::{JSRuntimeError, JSRuntimeThread};
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Submits a job that runs until `release` is sent something, and waits for it
+/// to start.
+fn blocking_job(runtime: &JSRuntimeThread) -> (mpsc::Sender<()>, impl Future<Output = Result<(), JSRuntimeError>>) {
+    let (started_sender, started) = mpsc::channel();
+    let (release, released) = mpsc::channel::<()>();
+    let future = runtime.run(move |_| {
+        started_sender.send(()).unwrap();
+        released.recv().unwrap();
+    });
+    started.recv().unwrap();
+    (release, future)
+}
+
+#[test]
+fn run_fails_when_the_queue_is_full() {
+    let runtime = JSRuntimeThread::with_capacity(2);
+    let (release, running) = blocking_job(&runtime);
+    let queued = runtime.run(|ctx| ctx.evaluate_script("1 + 1", 1).unwrap().to_number(ctx).unwrap());
+    assert_eq!(block_on(runtime.run(|_| ())), Err(JSRuntimeError::QueueFull));
+
+    release.send(()).unwrap();
+    assert_eq!(block_on(running), Ok(()));
+    assert_eq!(block_on(queued), Ok(2.0));
+    // The slots are free again once the jobs are done.
+    assert_eq!(block_on(runtime.run(|_| 3)), Ok(3));
+}
+
+#[test]
+fn run_blocking_waits_for_a_free_slot() {
+    let runtime = JSRuntimeThread::with_capacity(1);
+    let (release, running) = blocking_job(&runtime);
+    thread::scope(|scope| {
+        let waiting = scope.spawn(|| runtime.run_blocking(|_| "done"));
+        thread::sleep(Duration::from_millis(50));
+        assert!(!waiting.is_finished());
+        release.send(()).unwrap();
+        assert_eq!(waiting.join().unwrap(), Ok("done"));
+    });
+    assert_eq!(block_on(running), Ok(()));
+}
+
+#[test]
+fn dropping_the_runtime_runs_the_pending_jobs() {
+    let runtime = JSRuntimeThread::new();
+    let (release, running) = blocking_job(&runtime);
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let pending: Vec<_> = (0..3)
+        .map(|i| {
+            let order = order.clone();
+            runtime.run(move |_| {
+                order.lock().unwrap().push(i);
+                i
+            })
+        })
+        .collect();
+
+    thread::scope(|scope| {
+        let dropping = scope.spawn(move || drop(runtime));
+        thread::sleep(Duration::from_millis(50));
+        release.send(()).unwrap();
+        dropping.join().unwrap();
+    });
+    assert_eq!(block_on(running), Ok(()));
+    let results: Vec<_> = pending.into_iter().map(block_on).collect();
+    assert_eq!(results, vec![Ok(0), Ok(1), Ok(2)]);
+    assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+}
+
+#[test]
+fn tasks_are_rejected_after_shutdown() {
+    let runtime = JSRuntimeThread::new();
+    let handle = runtime.handle();
+    assert!(!handle.is_closed());
+    runtime.shutdown();
+    assert!(handle.is_closed());
+    assert!(handle.post(|_| unreachable!()).is_err());
+}
+
+#[test]
+fn panicking_jobs_are_reported_and_the_runtime_keeps_running() {
+    let runtime = JSRuntimeThread::with_capacity(1);
+    assert_eq!(
+        runtime.run_blocking(|_| panic!("static message")),
+        Err(JSRuntimeError::Panicked("static message".to_string()))
+    );
+    let code = 7;
+    assert_eq!(
+        block_on(runtime.run(move |_| panic!("formatted message {}", code))),
+        Err(JSRuntimeError::Panicked("formatted message 7".to_string()))
+    );
+    // The slots of the panicking jobs were freed.
+    let value = runtime.run_blocking(|ctx| ctx.evaluate_script("40 + 2", 1).unwrap().to_number(ctx).unwrap());
+    assert_eq!(value, Ok(42.0));
+}
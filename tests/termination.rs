@@ -0,0 +1,95 @@
+use std::thread;
+use std::time::Duration;
+
+use rusty_jsc::{callback, JSContext, JSException, JSObject, JSValue, TerminationReason};
+
+fn spin(context: &JSContext) -> JSObject {
+    context
+        .evaluate_script("(function () { for (;;) {} })", 1)
+        .unwrap()
+        .to_object(context)
+        .unwrap()
+}
+
+fn assert_usable(context: &JSContext) {
+    let value = context.evaluate_script("1 + 1", 1).unwrap();
+    assert_eq!(value.to_number(context).unwrap(), 2.0);
+}
+
+/// Calls its argument, propagating its error like any callback would.
+#[callback]
+fn call_back(ctx: JSContext, args: &[JSValue]) -> Result<JSValue, JSException> {
+    let function = args[0].to_object(&ctx)?;
+    function.call(&ctx, None, &[])
+}
+
+#[test]
+fn terminations_through_callbacks_cannot_be_caught() {
+    let context = JSContext::default();
+    // Only reached if the script escapes the termination request.
+    context.set_execution_time_limit(Some(Duration::from_secs(5)));
+    context
+        .get_global_object()
+        .set_property(&context, "callBack", JSValue::callback(&context, Some(call_back)))
+        .unwrap();
+    let handle = context.termination_handle();
+    let terminator = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.terminate();
+    });
+    let error = context
+        .evaluate_script(
+            "for (;;) { try { callBack(() => { for (;;) {} }); } catch (e) {} }",
+            1,
+        )
+        .unwrap_err();
+    terminator.join().unwrap();
+    assert_eq!(error.termination(), Some(TerminationReason::Requested));
+    assert_usable(&context);
+}
+
+#[test]
+fn time_limit_applies_to_calls_from_rust() {
+    let context = JSContext::default();
+    context.set_execution_time_limit(Some(Duration::from_millis(50)));
+    let error = spin(&context).call(&context, None, &[]).unwrap_err();
+    assert_eq!(error.termination(), Some(TerminationReason::TimedOut));
+    assert_usable(&context);
+}
+
+#[test]
+fn time_limit_applies_to_constructors_called_from_rust() {
+    let context = JSContext::default();
+    context.set_execution_time_limit(Some(Duration::from_millis(50)));
+    let error = spin(&context).construct(&context, &[]).unwrap_err();
+    assert_eq!(error.termination(), Some(TerminationReason::TimedOut));
+    assert_usable(&context);
+}
+
+#[test]
+fn terminate_aborts_a_call_from_rust() {
+    let context = JSContext::default();
+    let handle = context.termination_handle();
+    let terminator = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.terminate();
+    });
+    let error = spin(&context).call(&context, None, &[]).unwrap_err();
+    terminator.join().unwrap();
+    assert_eq!(error.termination(), Some(TerminationReason::Requested));
+    assert_usable(&context);
+}
+
+#[test]
+fn terminate_without_a_running_script_has_no_effect() {
+    let context = JSContext::default();
+    context.termination_handle().terminate();
+    assert_usable(&context);
+    let identity = context
+        .evaluate_script("(x => x)", 1)
+        .unwrap()
+        .to_object(&context)
+        .unwrap();
+    context.termination_handle().terminate();
+    assert!(identity.call(&context, None, &[]).is_ok());
+}
@@ -0,0 +1,82 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use rusty_jsc::private::{JSContextRef, JSGlobalContextCreate, JSGlobalContextRelease};
+use rusty_jsc::JSContext;
+
+struct Counter(u32);
+
+/// Records when it is dropped.
+struct Tracked(Rc<Cell<bool>>);
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn user_data_is_per_type_and_per_context() {
+    let context = JSContext::default();
+    let sibling = context.new_sibling();
+    context.set_user_data(Counter(1));
+    context.set_user_data(String::from("name"));
+    assert_eq!(context.user_data::<Counter>().unwrap().borrow().0, 1);
+    assert_eq!(*context.user_data::<String>().unwrap().borrow(), "name");
+    assert!(sibling.user_data::<Counter>().is_none());
+
+    context.set_user_data(Counter(2));
+    assert_eq!(context.user_data::<Counter>().unwrap().borrow().0, 2);
+    assert_eq!(context.remove_user_data::<Counter>().unwrap().borrow().0, 2);
+    assert!(context.user_data::<Counter>().is_none());
+}
+
+#[test]
+fn user_data_outlives_the_wrappers_of_the_context() {
+    let dropped = Rc::new(Cell::new(false));
+    let raw = unsafe { JSGlobalContextCreate(std::ptr::null_mut()) };
+    {
+        let context = JSContext::from(raw as JSContextRef);
+        context.set_user_data(Counter(7));
+        context.set_user_data(Tracked(dropped.clone()));
+    }
+    {
+        // Every wrapper is gone, but the context is still alive.
+        let context = JSContext::from(raw as JSContextRef);
+        assert_eq!(context.user_data::<Counter>().unwrap().borrow().0, 7);
+    }
+    assert!(!dropped.get());
+    unsafe { JSGlobalContextRelease(raw) };
+    assert!(dropped.get());
+}
+
+#[test]
+fn scripts_cannot_see_or_replace_user_data() {
+    let raw = unsafe { JSGlobalContextCreate(std::ptr::null_mut()) };
+    JSContext::from(raw as JSContextRef).set_user_data(Counter(3));
+    JSContext::from(raw as JSContextRef)
+        .evaluate_script(
+            "for (const name of Object.getOwnPropertyNames(globalThis)) { \
+                 try { delete globalThis[name]; } catch (e) {} \
+                 try { globalThis[name] = {}; } catch (e) {} \
+             }",
+            1,
+        )
+        .unwrap();
+    let context = JSContext::from(raw as JSContextRef);
+    assert_eq!(context.user_data::<Counter>().unwrap().borrow().0, 3);
+    let before = context
+        .evaluate_script("Reflect.ownKeys(globalThis).length", 1)
+        .unwrap()
+        .to_number(&context)
+        .unwrap();
+    context.set_user_data(String::from("more"));
+    let after = context
+        .evaluate_script("Reflect.ownKeys(globalThis).length", 1)
+        .unwrap()
+        .to_number(&context)
+        .unwrap();
+    assert_eq!(before, after);
+    drop(context);
+    unsafe { JSGlobalContextRelease(raw) };
+}